@@ -0,0 +1,96 @@
+new-marker = 🆕
+urgent-marker = ❗
+
+pending-reviews-title = 🔎 Du hast { $count } { $count ->
+        [one] ausstehendes Review
+       *[other] ausstehende Reviews
+    }:
+pending-reviews-waiting = Wartet seit { $days } { $days ->
+        [one] Tag
+       *[other] Tagen
+    }
+pending-reviews-closing = Mach das Team glücklich und bearbeite das zeitnah!
+
+inactive-cards-title = 📝 Folgende { $count } { $count ->
+        [one] Karte ist
+       *[other] Karten sind
+    } seit längerer Zeit im Sprint:
+inactive-cards-in-list-since = In Liste seit { $age }
+inactive-cards-closing = Schau mal nach, ob die Karten zu bearbeiten sind!
+
+age-weeks = { $value } { $value ->
+        [one] Woche
+       *[other] Wochen
+    }
+age-days = { $value } { $value ->
+        [one] Tag
+       *[other] Tagen
+    }
+age-hours = { $value } { $value ->
+        [one] Stunde
+       *[other] Stunden
+    }
+
+open-in-trello = In Trello öffnen
+more-cards = … und { $count } { $count ->
+        [one] weitere Karte
+       *[other] weitere Karten
+    }
+
+snooze-day = 💤 1 Tag pausieren
+snooze-monday = 💤 Bis Montag pausieren
+acknowledge = 👍 Bin dran
+snoozed-until = 💤 Du wirst bis { $until } nicht an diese Karte erinnert.
+acknowledged = 👍 Du wirst nicht an diese Karte erinnert, solange sie in der Liste bleibt.
+
+command-unknown-user = Deinem Slack-Account ist kein Trello-Benutzer zugeordnet.
+no-pending-reviews = 🎉 Du hast keine ausstehenden Reviews.
+no-inactive-cards = 🎉 Du hast keine inaktiven Karten.
+command-failed = ⚠️ Deine Karten konnten nicht von Trello abgerufen werden, bitte versuche es später noch einmal.
+
+pending-reviews-digest-title = 🔎 Im Team { $count ->
+        [one] wartet { $count } Review
+       *[other] warten { $count } Reviews
+    }:
+inactive-cards-digest-title = 📝 Im Team { $count ->
+        [one] ist { $count } Karte
+       *[other] sind { $count } Karten
+    } seit längerer Zeit im Sprint:
+digest-card-count = { $count } { $count ->
+        [one] Karte
+       *[other] Karten
+    }
+digest-oldest = ⏳ Am längsten wartet:
+
+due-dates-title = ⏰ Du hast { $count } { $count ->
+        [one] Karte
+       *[other] Karten
+    } mit nahendem Fälligkeitsdatum:
+due-dates-overdue = 🚨 Überfällig
+due-dates-today = 📅 Heute fällig
+due-dates-this-week = 🗓️ Diese Woche fällig
+due-dates-due = Fällig am { $due }
+due-dates-closing = Bitte schließ die Karten rechtzeitig ab oder vereinbare ein neues Fälligkeitsdatum!
+no-due-cards = 🎉 Du hast keine bald fälligen Karten.
+
+blocked-cards-title = 🧱 Folgende { $count } { $count ->
+        [one] Karte ist
+       *[other] Karten sind
+    } blockiert:
+blocked-cards-closing = Bitte löse die Blockaden oder hol dir Hilfe im Team!
+no-blocked-cards = 🎉 Du hast keine blockierten Karten.
+
+wip-limits-title = 🚦 { $count ->
+        [one] Ein WIP-Limit ist
+       *[other] { $count } WIP-Limits sind
+    } überschritten:
+wip-limits-count = { $count } Karten bei einem Limit von { $limit }
+wip-limits-recent = Zuletzt hinzugekommen:
+wip-limits-closing = Lasst uns die angefangene Arbeit abschließen, bevor wir neue Karten beginnen!
+
+unassigned-cards-title = 👤 { $count ->
+        [one] Eine Karte hat
+       *[other] { $count } Karten haben
+    } keine Mitglieder:
+unassigned-cards-owner = Verantwortlich
+unassigned-cards-closing = Bitte weist die Karten jemandem zu!
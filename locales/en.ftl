@@ -0,0 +1,96 @@
+new-marker = 🆕
+urgent-marker = ❗
+
+pending-reviews-title = 🔎 You have { $count } pending { $count ->
+        [one] review
+       *[other] reviews
+    }:
+pending-reviews-waiting = Waiting for { $days } { $days ->
+        [one] day
+       *[other] days
+    }
+pending-reviews-closing = Make the team happy and take care of it soon!
+
+inactive-cards-title = 📝 The following { $count ->
+        [one] card has
+       *[other] { $count } cards have
+    } been in the sprint for a long time:
+inactive-cards-in-list-since = In list for { $age }
+inactive-cards-closing = Please check whether the cards still need some work!
+
+age-weeks = { $value } { $value ->
+        [one] week
+       *[other] weeks
+    }
+age-days = { $value } { $value ->
+        [one] day
+       *[other] days
+    }
+age-hours = { $value } { $value ->
+        [one] hour
+       *[other] hours
+    }
+
+open-in-trello = Open in Trello
+more-cards = … and { $count } more { $count ->
+        [one] card
+       *[other] cards
+    }
+
+snooze-day = 💤 Snooze 1 day
+snooze-monday = 💤 Snooze until Monday
+acknowledge = 👍 I'm on it
+snoozed-until = 💤 You will not be reminded of this card until { $until }.
+acknowledged = 👍 You will not be reminded of this card while it stays in the list.
+
+command-unknown-user = There is no Trello user mapped to your Slack account.
+no-pending-reviews = 🎉 You have no pending reviews.
+no-inactive-cards = 🎉 You have no inactive cards.
+command-failed = ⚠️ Your cards could not be fetched from Trello, please try again later.
+
+pending-reviews-digest-title = 🔎 The team has { $count } pending { $count ->
+        [one] review
+       *[other] reviews
+    }:
+inactive-cards-digest-title = 📝 { $count ->
+        [one] { $count } card of the team has
+       *[other] { $count } cards of the team have
+    } been in the sprint for a long time:
+digest-card-count = { $count } { $count ->
+        [one] card
+       *[other] cards
+    }
+digest-oldest = ⏳ Waiting the longest:
+
+due-dates-title = ⏰ You have { $count } { $count ->
+        [one] card
+       *[other] cards
+    } with an upcoming due date:
+due-dates-overdue = 🚨 Overdue
+due-dates-today = 📅 Due today
+due-dates-this-week = 🗓️ Due this week
+due-dates-due = Due { $due }
+due-dates-closing = Please finish the cards in time or agree on a new due date!
+no-due-cards = 🎉 You have no cards due soon.
+
+blocked-cards-title = 🧱 The following { $count ->
+        [one] card is
+       *[other] { $count } cards are
+    } blocked:
+blocked-cards-closing = Please resolve the blockers or ask the team for help!
+no-blocked-cards = 🎉 You have no blocked cards.
+
+wip-limits-title = 🚦 { $count ->
+        [one] A WIP limit is
+       *[other] { $count } WIP limits are
+    } exceeded:
+wip-limits-count = { $count } cards with a limit of { $limit }
+wip-limits-recent = Added most recently:
+wip-limits-closing = Let's finish the work in progress before starting new cards!
+
+unassigned-cards-title = 👤 { $count ->
+        [one] A card has
+       *[other] { $count } cards have
+    } no members:
+unassigned-cards-owner = Owner
+unassigned-cards-closing = Please assign the cards to someone!
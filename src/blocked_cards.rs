@@ -0,0 +1,267 @@
+use crate::{
+    ActionContext, BoardList, InactiveCard, InactiveCardContext, InactiveCardsContext, Recipient,
+    TrelloUser, UserContext,
+    blocks::blocked_cards_blocks,
+    calendar::WorkCalendar,
+    card_assignees,
+    config::{ActionConfig, LabelConfig, MessageFormat, Threshold, UnassignedConfig},
+    notify_recipients,
+    schema::Card,
+    slack::Message,
+    state::StateStore,
+    template::{MessageTemplate, Templates},
+    trello::{TrelloClient, moved_to_list_date},
+};
+use anyhow::Result;
+use log::info;
+use std::collections::HashMap;
+
+/// ACTION: Send reminders of cards with a blocked label, regardless of how long they are in their list
+pub fn blocked_cards(
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = (BoardList, Threshold)>,
+) -> Result<()> {
+    let ActionContext {
+        trello_client,
+        slack_poster: _,
+        slack_directory: _,
+        calendar,
+        templates,
+        message_format,
+        snooze_buttons,
+        locale: _,
+        digest: _,
+        labels,
+        unassigned,
+        boards: _,
+    } = *context;
+
+    let blocked_cards = get_blocked_cards(
+        trello_client,
+        calendar,
+        labels,
+        unassigned,
+        trello_member_id_to_username,
+        target_lists,
+    )?;
+
+    let action = ActionConfig::BlockedCards.to_string();
+    let report = notify_recipients(
+        context,
+        state,
+        &action,
+        trello_to_slack_mapping,
+        blocked_cards,
+        "no-blocked-cards",
+        |recipient, trello_user, blocked_cards| {
+            compose_blocked_cards_message(
+                templates,
+                message_format,
+                snooze_buttons,
+                calendar,
+                recipient,
+                trello_user,
+                blocked_cards,
+            )
+        },
+    )?;
+
+    report.finish()
+}
+
+/// Cards with a blocked label per member or list owner, with the time they are in their list.
+/// The threshold of the list only determines the sirens.
+fn get_blocked_cards(
+    trello_client: &TrelloClient,
+    calendar: &WorkCalendar,
+    labels: &LabelConfig,
+    unassigned: &UnassignedConfig,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = (BoardList, Threshold)>,
+) -> Result<HashMap<TrelloUser, Vec<InactiveCard>>> {
+    let mut blocked_cards = HashMap::<TrelloUser, Vec<InactiveCard>>::new();
+
+    for (board_list, threshold) in target_lists {
+        let list = &board_list.list;
+        info!("Processing list '{}' (ID: {})", list.name, list.id);
+
+        let cards = trello_client.get_cards(&list.id)?;
+
+        for card in &cards {
+            let Some(blocked_card) = blocked_card(card, &board_list, threshold, calendar, labels)?
+            else {
+                continue;
+            };
+
+            let trello_users =
+                card_assignees(card, &board_list, unassigned, trello_member_id_to_username);
+            if trello_users.is_empty() {
+                continue;
+            }
+
+            for trello_user in trello_users {
+                blocked_cards
+                    .entry(trello_user)
+                    .or_default()
+                    .push(blocked_card.clone());
+            }
+        }
+    }
+
+    Ok(blocked_cards)
+}
+
+/// The card as listed in the reminders, unless it has no blocked label or is excluded by its labels
+fn blocked_card(
+    card: &Card,
+    board_list: &BoardList,
+    threshold: Threshold,
+    calendar: &WorkCalendar,
+    labels: &LabelConfig,
+) -> Result<Option<InactiveCard>> {
+    if !labels.includes(card) || !labels.is_blocked(card) {
+        return Ok(None);
+    }
+
+    Ok(Some(InactiveCard {
+        card_id: card.id.clone(),
+        card_name: card.name.clone(),
+        card_url: card.url.clone(),
+        board_id: board_list.board_id.clone(),
+        board_name: board_list.board_name.clone(),
+        list_id: board_list.list.id.clone(),
+        list_name: board_list.list.name.clone(),
+        pending_since: calendar.working_time_since(moved_to_list_date(card)?),
+        threshold: calendar.threshold_duration(threshold),
+        is_new: false,
+        is_urgent: labels.is_urgent(card),
+    }))
+}
+
+pub fn compose_blocked_cards_message(
+    templates: &Templates,
+    message_format: MessageFormat,
+    snooze_buttons: bool,
+    calendar: &WorkCalendar,
+    recipient: &Recipient,
+    trello_user: &TrelloUser,
+    mut blocked_cards: Vec<InactiveCard>,
+) -> Result<Message> {
+    blocked_cards.sort_by_key(|card| std::cmp::Reverse(card.pending_since)); // descending
+
+    let translator = templates.translator(recipient.locale);
+    let blocks = (message_format == MessageFormat::Blocks)
+        .then(|| blocked_cards_blocks(translator, calendar, &blocked_cards, snooze_buttons));
+    let cards: Vec<InactiveCardContext> = blocked_cards
+        .into_iter()
+        .map(|card| InactiveCardContext::new(translator, calendar, card))
+        .collect();
+
+    let text = templates.render(
+        MessageTemplate::BlockedCards,
+        recipient.locale,
+        InactiveCardsContext {
+            user: UserContext::new(recipient, trello_user),
+            count: cards.len(),
+            cards,
+        },
+    )?;
+
+    Ok(Message { text, blocks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::List;
+    use time::Weekday;
+
+    fn card(id: &str, name: &str, labels: &[&str]) -> Card {
+        let labels: Vec<_> = labels
+            .iter()
+            .map(|label| serde_json::json!({ "id": label, "name": label, "color": null }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "idList": "doing",
+            "idMembers": ["m1"],
+            "name": name,
+            "dateLastActivity": "2025-06-02T10:00:00Z",
+            "actions": [],
+            "url": format!("https://trello.com/c/{id}"),
+            "labels": labels,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_reminds_of_the_cards_with_a_blocked_label() {
+        let calendar = WorkCalendar::new(&[Weekday::Monday], [], chrono_tz::UTC).unwrap();
+        let board_list = BoardList {
+            board_id: "board1".to_string(),
+            board_name: "Team".to_string(),
+            list: List {
+                id: "doing".to_string(),
+                name: "Doing".to_string(),
+            },
+        };
+        let names = |names: &[&str]| names.iter().map(ToString::to_string).collect();
+        let labels = LabelConfig {
+            include: Vec::new(),
+            exclude: names(&["on-hold"]),
+            urgent: names(&["urgent"]),
+            blocked: names(&["Blocked"]),
+        };
+        let threshold = Threshold {
+            weeks: 1,
+            ..Threshold::default()
+        };
+
+        let blocked_cards: Vec<InactiveCard> = [
+            card("5f1d7c3e9a2b4c0012345678", "Plain", &[]),
+            card("5f1d7c3e9a2b4c0012345679", "Blocked", &["blocked"]),
+            card("5f1d7c3e9a2b4c001234567a", "Urgent", &["blocked", "urgent"]),
+            card(
+                "5f1d7c3e9a2b4c001234567b",
+                "On hold",
+                &["blocked", "on-hold"],
+            ),
+        ]
+        .iter()
+        .filter_map(|card| blocked_card(card, &board_list, threshold, &calendar, &labels).unwrap())
+        .collect();
+
+        let summary: Vec<(&str, bool)> = blocked_cards
+            .iter()
+            .map(|card| (card.card_name.as_str(), card.is_urgent))
+            .collect();
+        assert_eq!(summary, [("Blocked", false), ("Urgent", true)]);
+
+        let templates = Templates::for_test(None);
+        let recipient = Recipient::for_test();
+        let message = compose_blocked_cards_message(
+            &templates,
+            MessageFormat::Markdown,
+            false,
+            &calendar,
+            &recipient,
+            &TrelloUser("alice".to_string()),
+            blocked_cards,
+        )
+        .unwrap();
+
+        assert!(
+            message
+                .text
+                .contains("- [Urgent](https://trello.com/c/5f1d7c3e9a2b4c001234567a) ❗ - "),
+            "{}",
+            message.text
+        );
+        assert!(message.text.contains("[Blocked]"), "{}", message.text);
+        assert!(!message.text.contains("On hold"), "{}", message.text);
+        assert!(message.blocks.is_none());
+    }
+}
@@ -0,0 +1,399 @@
+use crate::{
+    InactiveCard, PendingReview,
+    calendar::WorkCalendar,
+    due_dates::DueCard,
+    format_age,
+    i18n::{Arg, Translator},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Slack accepts at most 50 blocks per message
+const MAX_BLOCKS: usize = 50;
+/// Header, divider, closing text and the hint about omitted cards
+const FRAME_BLOCKS: usize = 4;
+
+/// Action IDs of the snooze buttons
+pub const SNOOZE_DAY: &str = "snooze_day";
+pub const SNOOZE_MONDAY: &str = "snooze_monday";
+pub const ACKNOWLEDGE: &str = "acknowledge";
+
+/// Value of the snooze buttons, identifying the card
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardReference {
+    pub card_id: String,
+    pub list_id: String,
+}
+
+/// Card of a Block Kit message: a section with the linked title and a button, followed by a context line
+/// and optionally the snooze buttons
+struct CardBlocks<'a> {
+    id: &'a str,
+    list_id: &'a str,
+    name: &'a str,
+    url: &'a str,
+    is_new: bool,
+    is_urgent: bool,
+    board_name: &'a str,
+    list_name: &'a str,
+    /// Age of the card and further hints, if any
+    details: Option<String>,
+}
+
+/// Block Kit blocks of a pending reviews message, expects the reviews in display order
+pub fn pending_reviews_blocks(
+    translator: &Translator,
+    reviews: &[PendingReview],
+    snooze_buttons: bool,
+) -> Value {
+    let title = translator.text(
+        "pending-reviews-title",
+        &[("count", Arg::Number(reviews.len() as i64))],
+    );
+    let cards = reviews.iter().map(|review| CardBlocks {
+        id: &review.card_id,
+        list_id: &review.list_id,
+        name: &review.card_name,
+        url: &review.card_url,
+        is_new: review.is_new,
+        is_urgent: review.is_urgent,
+        board_name: &review.board_name,
+        list_name: &review.list_name,
+        details: (review.pending_since_days >= 1).then(|| {
+            format!(
+                "{} {}",
+                translator.text(
+                    "pending-reviews-waiting",
+                    &[("days", Arg::Number(review.pending_since_days as i64))]
+                ),
+                "🚨".repeat(review.pending_since_days.saturating_sub(1))
+            )
+        }),
+    });
+
+    message_blocks(
+        translator,
+        &title,
+        cards,
+        reviews.len(),
+        &translator.text("pending-reviews-closing", &[]),
+        snooze_buttons,
+    )
+}
+
+/// Block Kit blocks of an inactive cards message, expects the cards in display order
+pub fn inactive_cards_blocks(
+    translator: &Translator,
+    calendar: &WorkCalendar,
+    inactive_cards: &[InactiveCard],
+    snooze_buttons: bool,
+) -> Value {
+    list_age_blocks(
+        translator,
+        calendar,
+        inactive_cards,
+        ("inactive-cards-title", "inactive-cards-closing"),
+        snooze_buttons,
+    )
+}
+
+/// Block Kit blocks of a blocked cards message, expects the cards in display order
+pub fn blocked_cards_blocks(
+    translator: &Translator,
+    calendar: &WorkCalendar,
+    blocked_cards: &[InactiveCard],
+    snooze_buttons: bool,
+) -> Value {
+    list_age_blocks(
+        translator,
+        calendar,
+        blocked_cards,
+        ("blocked-cards-title", "blocked-cards-closing"),
+        snooze_buttons,
+    )
+}
+
+/// Blocks of cards with the time they are in their list, with the ids of the title and closing texts
+fn list_age_blocks(
+    translator: &Translator,
+    calendar: &WorkCalendar,
+    inactive_cards: &[InactiveCard],
+    (title_id, closing_id): (&str, &str),
+    snooze_buttons: bool,
+) -> Value {
+    let title = translator.text(
+        title_id,
+        &[("count", Arg::Number(inactive_cards.len() as i64))],
+    );
+    let cards = inactive_cards.iter().map(|card| CardBlocks {
+        id: &card.card_id,
+        list_id: &card.list_id,
+        name: &card.card_name,
+        url: &card.card_url,
+        is_new: card.is_new,
+        is_urgent: card.is_urgent,
+        board_name: &card.board_name,
+        list_name: &card.list_name,
+        details: Some(format!(
+            "{} {}",
+            translator.text(
+                "inactive-cards-in-list-since",
+                &[(
+                    "age",
+                    Arg::Text(format_age(translator, calendar, card.pending_since))
+                )]
+            ),
+            "🚨".repeat(card.sirens(calendar))
+        )),
+    });
+
+    message_blocks(
+        translator,
+        &title,
+        cards,
+        inactive_cards.len(),
+        &translator.text(closing_id, &[]),
+        snooze_buttons,
+    )
+}
+
+/// Block Kit blocks of a due dates message, expects the cards in display order
+pub fn due_dates_blocks(
+    translator: &Translator,
+    due_cards: &[DueCard],
+    snooze_buttons: bool,
+) -> Value {
+    let title = translator.text(
+        "due-dates-title",
+        &[("count", Arg::Number(due_cards.len() as i64))],
+    );
+    let cards = due_cards.iter().map(|card| CardBlocks {
+        id: &card.card_id,
+        list_id: &card.list_id,
+        name: &card.card_name,
+        url: &card.card_url,
+        is_new: card.is_new,
+        is_urgent: card.is_urgent,
+        board_name: &card.board_name,
+        list_name: &card.list_name,
+        details: Some(format!(
+            "{} · {} {}",
+            translator.text(card.group.message_id(), &[]),
+            translator.text(
+                "due-dates-due",
+                &[("due", Arg::Text(card.local_due.clone()))]
+            ),
+            "🚨".repeat(card.sirens())
+        )),
+    });
+
+    message_blocks(
+        translator,
+        &title,
+        cards,
+        due_cards.len(),
+        &translator.text("due-dates-closing", &[]),
+        snooze_buttons,
+    )
+}
+
+fn message_blocks<'a>(
+    translator: &Translator,
+    title: &str,
+    cards: impl Iterator<Item = CardBlocks<'a>>,
+    count: usize,
+    closing: &str,
+    snooze_buttons: bool,
+) -> Value {
+    let blocks_per_card = if snooze_buttons { 3 } else { 2 };
+    let max_cards = (MAX_BLOCKS - FRAME_BLOCKS) / blocks_per_card;
+
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": title, "emoji": true },
+        }),
+        json!({ "type": "divider" }),
+    ];
+
+    for card in cards.take(max_cards) {
+        blocks.extend(card_blocks(translator, &card));
+        if snooze_buttons {
+            blocks.push(snooze_buttons_block(translator, &card));
+        }
+    }
+
+    if count > max_cards {
+        blocks.push(json!({
+            "type": "context",
+            "elements": [mrkdwn(&translator.text(
+                "more-cards",
+                &[("count", Arg::Number((count - max_cards) as i64))],
+            ))],
+        }));
+    }
+
+    blocks.push(json!({ "type": "section", "text": mrkdwn(closing) }));
+
+    Value::Array(blocks)
+}
+
+fn card_blocks(translator: &Translator, card: &CardBlocks) -> [Value; 2] {
+    let mut title = format!("*<{}|{}>*", card.url, escape(card.name));
+    if card.is_new {
+        title.push(' ');
+        title.push_str(&translator.text("new-marker", &[]));
+    }
+    if card.is_urgent {
+        title.push(' ');
+        title.push_str(&translator.text("urgent-marker", &[]));
+    }
+
+    let mut context = Vec::new();
+    if let Some(details) = &card.details {
+        context.push(mrkdwn(details.trim_end()));
+    }
+    context.push(mrkdwn(&format!(
+        "📋 {} › {}",
+        escape(card.board_name),
+        escape(card.list_name)
+    )));
+
+    [
+        json!({
+            "type": "section",
+            "text": mrkdwn(&title),
+            "accessory": {
+                "type": "button",
+                "text": {
+                    "type": "plain_text",
+                    "text": translator.text("open-in-trello", &[]),
+                    "emoji": true,
+                },
+                "url": card.url,
+                "action_id": "open_in_trello",
+            },
+        }),
+        json!({ "type": "context", "elements": context }),
+    ]
+}
+
+fn snooze_buttons_block(translator: &Translator, card: &CardBlocks) -> Value {
+    let value = serde_json::to_string(&CardReference {
+        card_id: card.id.to_string(),
+        list_id: card.list_id.to_string(),
+    })
+    .unwrap_or_default();
+
+    let button = |action_id: &str, message_id: &str| {
+        json!({
+            "type": "button",
+            "action_id": action_id,
+            "text": {
+                "type": "plain_text",
+                "text": translator.text(message_id, &[]),
+                "emoji": true,
+            },
+            "value": value,
+        })
+    };
+
+    json!({
+        "type": "actions",
+        "elements": [
+            button(SNOOZE_DAY, "snooze-day"),
+            button(SNOOZE_MONDAY, "snooze-monday"),
+            button(ACKNOWLEDGE, "acknowledge"),
+        ],
+    })
+}
+
+fn mrkdwn(text: &str) -> Value {
+    json!({ "type": "mrkdwn", "text": text })
+}
+
+/// Escapes the control characters of Slack's mrkdwn, cf. <https://api.slack.com/reference/surfaces/formatting#escaping>
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Locale;
+
+    #[test]
+    fn it_renders_a_section_per_card() {
+        let translator = Translator::new(Locale::En);
+
+        let reviews = [
+            PendingReview {
+                card_url: "https://trello.com/c/abc".to_string(),
+                ..PendingReview::for_test("A <B>", "Review", 2)
+            },
+            PendingReview::for_test("C", "Review", 0),
+        ];
+        let blocks = pending_reviews_blocks(&translator, &reviews, false);
+
+        assert_eq!(blocks[0]["text"]["text"], "🔎 You have 2 pending reviews:");
+        assert_eq!(
+            blocks[2]["text"]["text"],
+            "*<https://trello.com/c/abc|A &lt;B&gt;>*"
+        );
+        assert_eq!(blocks[2]["accessory"]["text"]["text"], "Open in Trello");
+        assert_eq!(blocks[3]["elements"][0]["text"], "Waiting for 2 days 🚨");
+        assert_eq!(blocks[3]["elements"][1]["text"], "📋 Team › Review");
+        assert_eq!(
+            blocks[5]["elements"].as_array().unwrap().len(),
+            1,
+            "no age for reviews of today"
+        );
+        assert_eq!(blocks.as_array().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn it_stays_within_the_block_limit() {
+        let translator = Translator::new(Locale::De);
+        let reviews: Vec<PendingReview> = (0..30)
+            .map(|i| PendingReview::for_test(&i.to_string(), "Review", 1))
+            .collect();
+
+        let blocks = pending_reviews_blocks(&translator, &reviews, false);
+        let blocks = blocks.as_array().unwrap();
+
+        assert_eq!(blocks.len(), MAX_BLOCKS);
+        assert_eq!(
+            blocks[MAX_BLOCKS - 2]["elements"][0]["text"],
+            "… und 7 weitere Karten"
+        );
+
+        let blocks = pending_reviews_blocks(&translator, &reviews, true);
+        assert!(blocks.as_array().unwrap().len() <= MAX_BLOCKS);
+    }
+
+    #[test]
+    fn it_adds_snooze_buttons() {
+        let translator = Translator::new(Locale::En);
+
+        let blocks = pending_reviews_blocks(
+            &translator,
+            &[PendingReview::for_test("A", "Review", 1)],
+            true,
+        );
+
+        let buttons = &blocks[4]["elements"];
+        assert_eq!(buttons[0]["action_id"], SNOOZE_DAY);
+        assert_eq!(buttons[2]["text"]["text"], "👍 I'm on it");
+        let card: CardReference =
+            serde_json::from_str(buttons[1]["value"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            card,
+            CardReference {
+                card_id: "A".to_string(),
+                list_id: "review".to_string(),
+            }
+        );
+    }
+}
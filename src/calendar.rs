@@ -0,0 +1,318 @@
+use crate::config::{CalendarConfig, Threshold};
+use anyhow::{Context, Result, bail};
+use chrono::{Offset, TimeZone};
+use chrono_tz::Tz;
+use std::{collections::BTreeSet, path::Path};
+use time::{Date, Duration, OffsetDateTime, UtcOffset, Weekday, macros::format_description};
+
+/// Measures the age of cards in working time, i.e. only counting time on working days that are no holidays.
+/// Day boundaries are midnights in the time zone of the team.
+#[derive(Clone, Debug)]
+pub struct WorkCalendar {
+    workdays: BTreeSet<u8>,
+    holidays: BTreeSet<Date>,
+    timezone: Tz,
+}
+
+impl WorkCalendar {
+    pub fn new(
+        workdays: &[Weekday],
+        holidays: impl IntoIterator<Item = Date>,
+        timezone: Tz,
+    ) -> Result<Self> {
+        if workdays.is_empty() {
+            bail!("At least one working day must be configured");
+        }
+
+        Ok(WorkCalendar {
+            workdays: workdays
+                .iter()
+                .map(|weekday| weekday.number_days_from_monday())
+                .collect(),
+            holidays: holidays.into_iter().collect(),
+            timezone,
+        })
+    }
+
+    pub fn from_config(config: &CalendarConfig, timezone: Tz) -> Result<Self> {
+        let mut holidays = config.holidays.clone();
+
+        if let Some(holiday_calendar) = &config.holiday_calendar {
+            holidays.extend(holidays_from_ical_file(holiday_calendar)?);
+        }
+
+        Self::new(&config.workdays, holidays, timezone)
+    }
+
+    pub fn is_working_day(&self, date: Date) -> bool {
+        self.workdays
+            .contains(&date.weekday().number_days_from_monday())
+            && !self.holidays.contains(&date)
+    }
+
+    /// Sums up the time between `start` and `end` that falls on working days
+    pub fn working_time_between(&self, start: OffsetDateTime, end: OffsetDateTime) -> Duration {
+        let mut working_time = Duration::ZERO;
+        let mut date = self.local_date(start);
+        let end_date = self.local_date(end);
+
+        while date <= end_date {
+            let next_date = date.next_day();
+
+            if self.is_working_day(date) {
+                let day_start = self.day_start(date).max(start);
+                let day_end = next_date
+                    .map_or(end, |next_date| self.day_start(next_date))
+                    .min(end);
+
+                if day_end > day_start {
+                    working_time += day_end - day_start;
+                }
+            }
+
+            let Some(next_date) = next_date else {
+                break;
+            };
+            date = next_date;
+        }
+
+        working_time
+    }
+
+    /// Calendar date of the time in the time zone
+    fn local_date(&self, time: OffsetDateTime) -> Date {
+        time.to_offset(self.offset_at(time)).date()
+    }
+
+    /// Local midnight of the date, or the first time of the date if the clocks are put forward at midnight
+    fn day_start(&self, date: Date) -> OffsetDateTime {
+        let midnight = date.midnight();
+        let guess = midnight.assume_offset(self.offset_at(midnight.assume_utc()));
+        midnight.assume_offset(self.offset_at(guess))
+    }
+
+    /// Offset of the time zone from UTC at the time
+    fn offset_at(&self, time: OffsetDateTime) -> UtcOffset {
+        let offset = self
+            .timezone
+            .timestamp_opt(time.unix_timestamp(), 0)
+            .single()
+            .map_or(0, |local| local.offset().fix().local_minus_utc());
+
+        UtcOffset::from_whole_seconds(offset).unwrap_or(UtcOffset::UTC)
+    }
+
+    /// Working time from `since` until now
+    pub fn working_time_since(&self, since: OffsetDateTime) -> Duration {
+        self.working_time_between(since, OffsetDateTime::now_utc())
+    }
+
+    /// Length of a working week, e.g. five days for a Monday to Friday week
+    pub fn working_week(&self) -> Duration {
+        Duration::days(self.workdays.len() as i64)
+    }
+
+    /// Converts a threshold into working time, counting a week as a working week
+    pub fn threshold_duration(&self, threshold: Threshold) -> Duration {
+        self.working_week() * threshold.weeks as i32
+            + Duration::days(threshold.days)
+            + Duration::hours(threshold.hours)
+    }
+}
+
+/// Reads the dates of all events of an iCalendar file, e.g. an export of public holidays.
+///
+/// All-day events cover every day from `DTSTART` up to (excluding) `DTEND`, other events only cover the day they start.
+/// Recurrence rules are not evaluated.
+pub fn holidays_from_ical_file(path: &Path) -> Result<Vec<Date>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read holiday calendar {}", path.display()))?;
+
+    holidays_from_ical(&content)
+        .with_context(|| format!("Could not parse holiday calendar {}", path.display()))
+}
+
+fn holidays_from_ical(content: &str) -> Result<Vec<Date>> {
+    // unfold continuation lines, cf. RFC 5545 section 3.1
+    let content = content
+        .replace("\r\n ", "")
+        .replace("\r\n\t", "")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut holidays = Vec::new();
+    let mut event: Option<(Option<Date>, Option<Date>)> = None;
+
+    for line in content.lines().map(str::trim_end) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (property, parameters) = name.split_once(';').unwrap_or((name, ""));
+        let is_all_day = parameters.contains("VALUE=DATE") && !parameters.contains("DATE-TIME");
+
+        match (property, &mut event) {
+            ("BEGIN", None) if value == "VEVENT" => event = Some((None, None)),
+            ("DTSTART", Some((start, _))) => *start = Some(parse_ical_date(value)?),
+            ("DTEND", Some((_, end))) if is_all_day => *end = Some(parse_ical_date(value)?),
+            ("END", Some((start, end))) if value == "VEVENT" => {
+                let Some(start) = *start else {
+                    bail!("Event without `DTSTART`");
+                };
+
+                let mut date = start;
+                loop {
+                    holidays.push(date);
+
+                    match (date.next_day(), *end) {
+                        (Some(next_date), Some(end)) if next_date < end => date = next_date,
+                        _ => break,
+                    }
+                }
+
+                event = None;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(holidays)
+}
+
+fn parse_ical_date(value: &str) -> Result<Date> {
+    let date = value.get(..8).context("Date value is too short")?;
+
+    Date::parse(date, format_description!("[year][month][day]"))
+        .with_context(|| format!("Invalid date `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+
+    fn monday_to_friday() -> [Weekday; 5] {
+        [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ]
+    }
+
+    #[test]
+    fn it_skips_weekends() {
+        let calendar = WorkCalendar::new(&monday_to_friday(), [], Tz::UTC).unwrap();
+
+        let working_time = calendar.working_time_between(
+            datetime!(2025-11-21 18:00:00 +00:00), // Friday
+            datetime!(2025-11-24 09:00:00 +00:00), // Monday
+        );
+
+        assert_eq!(working_time, Duration::hours(15));
+    }
+
+    #[test]
+    fn it_skips_holidays() {
+        let calendar = WorkCalendar::new(
+            &monday_to_friday(),
+            [date!(2025 - 12 - 25), date!(2025 - 12 - 26)],
+            Tz::UTC,
+        )
+        .unwrap();
+
+        let working_time = calendar.working_time_between(
+            datetime!(2025-12-24 00:00:00 +00:00), // Wednesday
+            datetime!(2025-12-30 00:00:00 +00:00), // Tuesday
+        );
+
+        assert_eq!(working_time, Duration::days(2));
+    }
+
+    #[test]
+    fn it_splits_days_in_the_time_zone() {
+        let calendar =
+            WorkCalendar::new(&monday_to_friday(), [], chrono_tz::Europe::Berlin).unwrap();
+
+        let working_time = |start, end| calendar.working_time_between(start, end);
+
+        // Friday 23:30 to Saturday 00:30 in Berlin (CET, UTC+1)
+        assert_eq!(
+            working_time(
+                datetime!(2025-11-21 22:30:00 +00:00),
+                datetime!(2025-11-21 23:30:00 +00:00)
+            ),
+            Duration::minutes(30)
+        );
+        // Sunday 23:00 to Monday 01:00 in Berlin
+        assert_eq!(
+            working_time(
+                datetime!(2025-11-23 22:00:00 +00:00),
+                datetime!(2025-11-24 00:00:00 +00:00)
+            ),
+            Duration::hours(1)
+        );
+        // the weekend of the change to summer time (CEST, UTC+2) starts at local midnight as well
+        assert_eq!(
+            working_time(
+                datetime!(2025-03-28 21:00:00 +00:00),
+                datetime!(2025-03-31 01:00:00 +00:00)
+            ),
+            Duration::hours(2) + Duration::hours(3)
+        );
+
+        let holiday = WorkCalendar::new(
+            &monday_to_friday(),
+            [date!(2025 - 12 - 25)],
+            chrono_tz::Europe::Berlin,
+        )
+        .unwrap();
+        assert_eq!(
+            holiday.working_time_between(
+                datetime!(2025-12-24 22:00:00 +00:00), // Wednesday 23:00 in Berlin
+                datetime!(2025-12-25 23:30:00 +00:00)  // Friday 00:30 in Berlin
+            ),
+            Duration::hours(1) + Duration::minutes(30),
+            "the holiday covers the local day"
+        );
+    }
+
+    #[test]
+    fn it_converts_thresholds_to_working_time() {
+        let calendar = WorkCalendar::new(&monday_to_friday(), [], Tz::UTC).unwrap();
+
+        assert_eq!(
+            calendar.threshold_duration("2w1d".parse().unwrap()),
+            Duration::days(11)
+        );
+    }
+
+    #[test]
+    fn it_reads_holidays_from_ical() {
+        let ical = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20251225\r\n\
+            DTEND;VALUE=DATE:20251227\r\n\
+            SUMMARY:Weihnachten\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20251231T000000Z\r\n\
+            DTEND:20260101T000000Z\r\n\
+            SUMMARY:Silve\r\n \
+            ster\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let holidays = holidays_from_ical(ical).unwrap();
+
+        assert_eq!(
+            holidays,
+            [
+                date!(2025 - 12 - 25),
+                date!(2025 - 12 - 26),
+                date!(2025 - 12 - 31)
+            ]
+        );
+    }
+}
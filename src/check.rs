@@ -0,0 +1,417 @@
+use crate::{
+    BoardList,
+    config::AppConfig,
+    list_selector::ListSelector,
+    schema::Member,
+    slack::SlackDirectory,
+    trello::TrelloClient,
+    user_mapping::{MappingSource, UserMappingTable},
+};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Outcome of the `check` command
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    boards: usize,
+    lists: usize,
+    members: usize,
+    pub problems: Vec<String>,
+}
+
+impl CheckReport {
+    pub fn render(&self) -> String {
+        let mut output = format!(
+            "Checked {} boards with {} lists and {} members.\n",
+            self.boards, self.lists, self.members
+        );
+
+        if self.problems.is_empty() {
+            output.push_str("No problems found.\n");
+            return output;
+        }
+
+        output.push_str("Found problems:\n");
+        for problem in &self.problems {
+            output.push_str("- ");
+            output.push_str(problem);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Validates the configuration against the current boards and the Slack workspace.
+/// Missing boards, lists, mappings and Slack IDs are collected as problems instead of failing.
+pub fn check_config(
+    config: &AppConfig,
+    trello_client: &TrelloClient,
+    slack_directory: &SlackDirectory,
+) -> Result<CheckReport> {
+    let mut report = CheckReport::default();
+    let mut members = HashSet::new();
+    let mut boards = Vec::new();
+    let mut board_lists = Vec::new();
+
+    for board_config in &config.boards {
+        if !config.trello.board_ids.contains(&board_config.id) {
+            report.problems.push(format!(
+                "Settings of board {} are ignored, since it is not one of the board IDs",
+                board_config.id
+            ));
+        }
+    }
+
+    for board_id in &config.trello.board_ids {
+        let board = match trello_client.get_board(board_id) {
+            Ok(board) => board,
+            Err(e) => {
+                report
+                    .problems
+                    .push(format!("Board {board_id} could not be fetched: {e:#}"));
+                continue;
+            }
+        };
+
+        let lists = trello_client.get_lists(board_id)?;
+        report.boards += 1;
+        report.lists += lists.len();
+        board_lists.extend(lists.into_iter().map(|list| BoardList {
+            board_id: board_id.clone(),
+            board_name: board.name.clone(),
+            list,
+        }));
+        boards.push((board_id.clone(), board.name));
+        members.extend(trello_client.get_members(board_id)?);
+    }
+
+    check_lists(config, &boards, &board_lists, &mut report);
+
+    let members: Vec<_> = members.into_iter().collect();
+    report.members = members.len();
+    check_user_mapping(config, &members, slack_directory, &mut report)?;
+    check_owners(config, &members, &mut report);
+
+    let digest_channels = [
+        &config.digest.pending_reviews_channel,
+        &config.digest.inactive_cards_channel,
+        &config.wip_limits.channel,
+        &config.unassigned.channel,
+    ];
+    let board_channels = config.boards.iter().flat_map(|board| board.channels.iter());
+    for channel in digest_channels.into_iter().flatten().chain(board_channels) {
+        if let Err(e) = slack_directory.check_channel(channel) {
+            report
+                .problems
+                .push(format!("Channel {channel} could not be resolved: {e:#}"));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Every list selector must match a list of each board it applies to.
+/// Names and IDs must match exactly one list, while patterns may match several.
+fn check_lists(
+    config: &AppConfig,
+    boards: &[(String, String)],
+    board_lists: &[BoardList],
+    report: &mut CheckReport,
+) {
+    let mut applied = HashSet::new();
+
+    for (board_id, board_name) in boards {
+        let location = format!("board {board_name} ({board_id})");
+        let lists: Vec<&BoardList> = board_lists
+            .iter()
+            .filter(|board_list| board_list.board_id == *board_id)
+            .collect();
+
+        let board_thresholds = config
+            .boards
+            .iter()
+            .filter(|board| board.id == *board_id)
+            .flat_map(|board| &board.inactive_list_thresholds)
+            .map(|list_threshold| &list_threshold.list);
+        let selectors = config
+            .review_lists(board_id)
+            .iter()
+            .map(|selector| ("Review list", selector))
+            .chain(
+                config
+                    .inactive_cards_lists(board_id)
+                    .iter()
+                    .map(|selector| ("Inactive cards list", selector)),
+            )
+            .chain(board_thresholds.map(|selector| ("List with a threshold", selector)))
+            .chain(
+                config
+                    .due_dates
+                    .lists
+                    .iter()
+                    .map(|selector| ("Due dates list", selector)),
+            )
+            .chain(wip_limit_selectors(config))
+            .chain(list_owner_selectors(config));
+
+        for (kind, selector) in selectors {
+            if !selector.applies_to(board_id, board_name) {
+                continue;
+            }
+            applied.insert(selector.to_string());
+            report
+                .problems
+                .extend(check_selector(kind, selector, &lists, &location));
+        }
+    }
+
+    // global thresholds apply to lists of any board
+    let all_lists: Vec<&BoardList> = board_lists.iter().collect();
+    for list_threshold in &config.trello.inactive_list_thresholds {
+        if !all_lists
+            .iter()
+            .any(|board_list| list_threshold.list.matches(board_list))
+        {
+            report.problems.push(missing_list(
+                "List with a threshold",
+                &list_threshold.list,
+                &all_lists,
+                "any board",
+            ));
+        }
+    }
+
+    let global_selectors = config
+        .trello
+        .review_lists
+        .iter()
+        .map(|selector| ("Review list", selector))
+        .chain(
+            config
+                .trello
+                .inactive_cards_lists
+                .iter()
+                .map(|selector| ("Inactive cards list", selector)),
+        )
+        .chain(
+            config
+                .due_dates
+                .lists
+                .iter()
+                .map(|selector| ("Due dates list", selector)),
+        )
+        .chain(wip_limit_selectors(config))
+        .chain(list_owner_selectors(config));
+    for (kind, selector) in global_selectors {
+        if selector.board().is_some() && !applied.contains(&selector.to_string()) {
+            report
+                .problems
+                .push(format!(
+                    "{kind} \"{selector}\" does not apply to any board, escape slashes in list names as \\/"
+                ));
+        }
+    }
+}
+
+fn wip_limit_selectors(config: &AppConfig) -> impl Iterator<Item = (&str, &ListSelector)> {
+    config
+        .wip_limits
+        .lists
+        .iter()
+        .chain(&config.wip_limits.members)
+        .map(|list_limit| ("List with a WIP limit", &list_limit.list))
+}
+
+fn list_owner_selectors(config: &AppConfig) -> impl Iterator<Item = (&str, &ListSelector)> {
+    config
+        .unassigned
+        .owners
+        .iter()
+        .map(|list_owner| ("List with an owner", &list_owner.list))
+}
+
+/// Every board member must be mapped, and the Slack users of the manual mapping must exist
+fn check_user_mapping(
+    config: &AppConfig,
+    members: &[Member],
+    slack_directory: &SlackDirectory,
+    report: &mut CheckReport,
+) -> Result<()> {
+    let user_mapping = UserMappingTable::resolve(config, members, slack_directory)?;
+
+    for unmatched in &user_mapping.unmatched {
+        let mut problem = format!(
+            "Trello user {} ({}) is not mapped to a Slack user",
+            unmatched.trello_user, unmatched.full_name
+        );
+        if !unmatched.candidates.is_empty() {
+            let candidates: Vec<&str> = unmatched
+                .candidates
+                .iter()
+                .map(|slack_user| slack_user.0.as_str())
+                .collect();
+            problem.push_str(", the name matches ");
+            problem.push_str(&candidates.join(", "));
+        }
+        report.problems.push(problem);
+    }
+
+    let mut checked = HashSet::new();
+    for resolved in &user_mapping.mappings {
+        let mapping = &resolved.mapping;
+        if resolved.source != MappingSource::Manual || !checked.insert(&mapping.slack_user) {
+            continue;
+        }
+        if let Err(e) = slack_directory.check_user(&mapping.slack_user) {
+            report.problems.push(format!(
+                "Slack user {} of Trello user {} could not be resolved: {e:#}",
+                mapping.slack_user, mapping.trello_user
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The owners of unassigned cards must be board members, so that they can be mapped
+fn check_owners(config: &AppConfig, members: &[Member], report: &mut CheckReport) {
+    let owners = config
+        .unassigned
+        .owners
+        .iter()
+        .map(|list_owner| &list_owner.owner)
+        .chain(&config.unassigned.fallback);
+
+    let mut checked = HashSet::new();
+    for owner in owners {
+        if checked.insert(owner) && !members.iter().any(|member| member.username == owner.0) {
+            report.problems.push(format!(
+                "Owner {owner} of unassigned cards is no board member"
+            ));
+        }
+    }
+}
+
+/// Problem with a list selector, unless it matches exactly one list or is a pattern matching any
+fn check_selector(
+    kind: &str,
+    selector: &ListSelector,
+    lists: &[&BoardList],
+    location: &str,
+) -> Option<String> {
+    match lists
+        .iter()
+        .filter(|board_list| selector.matches(board_list))
+        .count()
+    {
+        0 => Some(missing_list(kind, selector, lists, location)),
+        1 => None,
+        _ if selector.is_pattern() => None,
+        count => Some(format!(
+            "{kind} \"{selector}\" matches {count} lists on {location}"
+        )),
+    }
+}
+
+fn missing_list(
+    kind: &str,
+    selector: &ListSelector,
+    lists: &[&BoardList],
+    location: &str,
+) -> String {
+    if selector.is_pattern() {
+        return format!("{kind} \"{selector}\" matches no list on {location}");
+    }
+
+    let list_names: Vec<&str> = lists
+        .iter()
+        .map(|board_list| board_list.list.name.as_str())
+        .collect();
+    match selector
+        .name()
+        .and_then(|name| suggestion(name, &list_names))
+    {
+        Some(suggestion) => format!(
+            "{kind} \"{selector}\" does not exist on {location}, did you mean \"{suggestion}\"?"
+        ),
+        None => format!("{kind} \"{selector}\" does not exist on {location}"),
+    }
+}
+
+/// The most similar name, if it differs by case or a few typos
+fn suggestion<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance of the characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::List;
+
+    #[test]
+    fn it_suggests_similar_list_names() {
+        let lists = ["Review", "In Progress", "Done"];
+
+        assert_eq!(suggestion("Reveiw", &lists), Some("Review"));
+        assert_eq!(suggestion("in progress", &lists), Some("In Progress"));
+        assert_eq!(suggestion("Backlog", &lists), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn it_requires_exactly_one_list_unless_the_selector_is_a_pattern() {
+        let board_list = |name: &str| BoardList {
+            board_id: "board1".to_string(),
+            board_name: "Team".to_string(),
+            list: List {
+                id: name.to_lowercase(),
+                name: name.to_string(),
+            },
+        };
+        let board_lists = [board_list("Review"), board_list("Done"), board_list("Done")];
+        let lists: Vec<&BoardList> = board_lists.iter().collect();
+        let check =
+            |selector: &str| check_selector("List", &selector.parse().unwrap(), &lists, "board");
+
+        assert_eq!(check("Review"), None);
+        assert_eq!(check("D*"), None);
+        assert_eq!(
+            check("review").as_deref(),
+            Some("List \"review\" does not exist on board, did you mean \"Review\"?")
+        );
+        assert_eq!(
+            check("Done").as_deref(),
+            Some("List \"Done\" matches 2 lists on board")
+        );
+        assert_eq!(
+            check("QA*").as_deref(),
+            Some("List \"QA*\" matches no list on board")
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    InactiveCard, InactiveCardContext, PendingReview, Recipient, TrelloUser,
+    calendar::WorkCalendar,
+    i18n::Locale,
+    slack::Message,
+    state::TrackedCard,
+    template::{MessageTemplate, Templates},
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use time::Duration;
+
+/// Card that can be part of a team digest
+pub trait DigestCard: TrackedCard + Clone {
+    fn board_id(&self) -> &str;
+    fn board_name(&self) -> &str;
+    fn list_name(&self) -> &str;
+    /// Time the card has been waiting, the oldest card of the digest is highlighted
+    fn age(&self) -> Duration;
+}
+
+impl DigestCard for PendingReview {
+    fn board_id(&self) -> &str {
+        &self.board_id
+    }
+
+    fn board_name(&self) -> &str {
+        &self.board_name
+    }
+
+    fn list_name(&self) -> &str {
+        &self.list_name
+    }
+
+    fn age(&self) -> Duration {
+        Duration::days(self.pending_since_days as i64)
+    }
+}
+
+impl DigestCard for InactiveCard {
+    fn board_id(&self) -> &str {
+        &self.board_id
+    }
+
+    fn board_name(&self) -> &str {
+        &self.board_name
+    }
+
+    fn list_name(&self) -> &str {
+        &self.list_name
+    }
+
+    fn age(&self) -> Duration {
+        self.pending_since
+    }
+}
+
+/// All cards of an action grouped by assignee and list, as provided to the digest templates
+#[derive(Debug, Serialize)]
+struct Digest<T> {
+    /// Number of distinct cards, cards with several assignees are listed for each of them
+    count: usize,
+    assignees: Vec<Assignee<T>>,
+    oldest: Option<Oldest<T>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Assignee<T> {
+    trello_user: String,
+    /// Only set for mapped Trello users
+    slack_user: Option<String>,
+    count: usize,
+    lists: Vec<DigestList<T>>,
+}
+
+#[derive(Debug, Serialize)]
+struct DigestList<T> {
+    board_name: String,
+    list_name: String,
+    cards: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+struct Oldest<T> {
+    card: T,
+    trello_user: String,
+    slack_user: Option<String>,
+}
+
+impl<C: DigestCard> Digest<C> {
+    /// Groups the cards by assignee, ordered by name, and list. The cards are ordered oldest first.
+    fn new(
+        cards_by_user: &HashMap<TrelloUser, Vec<C>>,
+        recipients: &HashMap<TrelloUser, Recipient>,
+    ) -> Self {
+        let mut trello_users: Vec<&TrelloUser> = cards_by_user
+            .iter()
+            .filter(|(_, cards)| !cards.is_empty())
+            .map(|(trello_user, _)| trello_user)
+            .collect();
+        trello_users.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let slack_user = |trello_user: &TrelloUser| {
+            recipients
+                .get(trello_user)
+                .map(|recipient| recipient.slack_user.0.clone())
+        };
+
+        let mut card_ids = HashSet::new();
+        let mut oldest: Option<Oldest<C>> = None;
+        let mut assignees = Vec::new();
+
+        for trello_user in trello_users {
+            let mut cards = cards_by_user[trello_user].clone();
+            cards.sort_by_key(|card| std::cmp::Reverse(card.age()));
+
+            if let Some(card) = cards.first()
+                && oldest
+                    .as_ref()
+                    .is_none_or(|oldest| card.age() > oldest.card.age())
+            {
+                oldest = Some(Oldest {
+                    card: card.clone(),
+                    trello_user: trello_user.0.clone(),
+                    slack_user: slack_user(trello_user),
+                });
+            }
+
+            let count = cards.len();
+            let mut lists: Vec<DigestList<C>> = Vec::new();
+            for card in cards {
+                card_ids.insert(card.card_id().to_string());
+
+                match lists.iter_mut().find(|list| {
+                    list.board_name == card.board_name() && list.list_name == card.list_name()
+                }) {
+                    Some(list) => list.cards.push(card),
+                    None => lists.push(DigestList {
+                        board_name: card.board_name().to_string(),
+                        list_name: card.list_name().to_string(),
+                        cards: vec![card],
+                    }),
+                }
+            }
+
+            assignees.push(Assignee {
+                trello_user: trello_user.0.clone(),
+                slack_user: slack_user(trello_user),
+                count,
+                lists,
+            });
+        }
+
+        Digest {
+            count: card_ids.len(),
+            assignees,
+            oldest,
+        }
+    }
+
+    /// Converts the cards, e.g. into their template context
+    fn map<T>(self, f: impl Fn(C) -> T) -> Digest<T> {
+        Digest {
+            count: self.count,
+            assignees: self
+                .assignees
+                .into_iter()
+                .map(|assignee| Assignee {
+                    trello_user: assignee.trello_user,
+                    slack_user: assignee.slack_user,
+                    count: assignee.count,
+                    lists: assignee
+                        .lists
+                        .into_iter()
+                        .map(|list| DigestList {
+                            board_name: list.board_name,
+                            list_name: list.list_name,
+                            cards: list.cards.into_iter().map(&f).collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            oldest: self.oldest.map(|oldest| Oldest {
+                card: f(oldest.card),
+                trello_user: oldest.trello_user,
+                slack_user: oldest.slack_user,
+            }),
+        }
+    }
+}
+
+/// Digests of the cards for the channel of their board, the cards of boards without a channel are left out
+pub fn compose_digests<'a, C: DigestCard>(
+    cards_by_user: &HashMap<TrelloUser, Vec<C>>,
+    channel: impl Fn(&str) -> Option<&'a String>,
+    compose: impl Fn(&HashMap<TrelloUser, Vec<C>>) -> Result<Option<Message>>,
+) -> Result<Vec<(&'a String, Message)>> {
+    let mut digests = Vec::new();
+    for (channel, cards_by_user) in cards_by_channel(cards_by_user, channel) {
+        digests.extend(compose(&cards_by_user)?.map(|message| (channel, message)));
+    }
+
+    Ok(digests)
+}
+
+/// Splits the cards by the channel of their board, the cards of boards without a channel are left out
+fn cards_by_channel<'a, C: DigestCard>(
+    cards_by_user: &HashMap<TrelloUser, Vec<C>>,
+    channel: impl Fn(&str) -> Option<&'a String>,
+) -> BTreeMap<&'a String, HashMap<TrelloUser, Vec<C>>> {
+    let mut cards_by_channel = BTreeMap::<&String, HashMap<TrelloUser, Vec<C>>>::new();
+    for (trello_user, cards) in cards_by_user {
+        for card in cards {
+            if let Some(channel) = channel(card.board_id()) {
+                cards_by_channel
+                    .entry(channel)
+                    .or_default()
+                    .entry(trello_user.clone())
+                    .or_default()
+                    .push(card.clone());
+            }
+        }
+    }
+
+    cards_by_channel
+}
+
+/// Digest of the pending reviews of all assignees, `None` if there are none
+pub fn compose_pending_reviews_digest(
+    templates: &Templates,
+    locale: Locale,
+    pending_reviews: &HashMap<TrelloUser, Vec<PendingReview>>,
+    recipients: &HashMap<TrelloUser, Recipient>,
+) -> Result<Option<Message>> {
+    let digest = Digest::new(pending_reviews, recipients);
+    if digest.count == 0 {
+        return Ok(None);
+    }
+
+    let text = templates.render(MessageTemplate::PendingReviewsDigest, locale, digest)?;
+
+    Ok(Some(Message { text, blocks: None }))
+}
+
+/// Digest of the inactive cards of all assignees, `None` if there are none
+pub fn compose_inactive_cards_digest(
+    templates: &Templates,
+    calendar: &WorkCalendar,
+    locale: Locale,
+    inactive_cards: &HashMap<TrelloUser, Vec<InactiveCard>>,
+    recipients: &HashMap<TrelloUser, Recipient>,
+) -> Result<Option<Message>> {
+    let digest = Digest::new(inactive_cards, recipients);
+    if digest.count == 0 {
+        return Ok(None);
+    }
+
+    let translator = templates.translator(locale);
+    let digest = digest.map(|card| InactiveCardContext::new(translator, calendar, card));
+    let text = templates.render(MessageTemplate::InactiveCardsDigest, locale, digest)?;
+
+    Ok(Some(Message { text, blocks: None }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_groups_the_cards_by_assignee_and_list() {
+        let alice = TrelloUser("alice".to_string());
+        let bob = TrelloUser("bob".to_string());
+        let cards_by_user = HashMap::from([
+            (
+                bob.clone(),
+                vec![
+                    PendingReview::for_test("shared", "Review", 2),
+                    PendingReview::for_test("b", "QA", 1),
+                ],
+            ),
+            (
+                alice.clone(),
+                vec![
+                    PendingReview::for_test("a", "Review", 1),
+                    PendingReview::for_test("old", "QA", 5),
+                    PendingReview::for_test("shared", "Review", 2),
+                ],
+            ),
+        ]);
+        let recipients = HashMap::from([(
+            alice,
+            Recipient {
+                slack_user: crate::SlackUser("U_ALICE".to_string()),
+                locale: Locale::En,
+            },
+        )]);
+
+        let digest = Digest::new(&cards_by_user, &recipients);
+
+        assert_eq!(digest.count, 4, "shared cards are counted once");
+        assert_eq!(digest.assignees[0].trello_user, "alice");
+        assert_eq!(digest.assignees[0].slack_user.as_deref(), Some("U_ALICE"));
+        assert_eq!(digest.assignees[0].count, 3);
+        let lists: Vec<(&str, Vec<&str>)> = digest.assignees[0]
+            .lists
+            .iter()
+            .map(|list| {
+                let cards = list.cards.iter().map(|card| card.card_id.as_str());
+                (list.list_name.as_str(), cards.collect())
+            })
+            .collect();
+        assert_eq!(
+            lists,
+            [("QA", vec!["old"]), ("Review", vec!["shared", "a"])]
+        );
+        assert_eq!(digest.assignees[1].slack_user, None);
+
+        let oldest = digest.oldest.unwrap();
+        assert_eq!(oldest.card.card_id, "old");
+        assert_eq!(oldest.trello_user, "alice");
+    }
+
+    #[test]
+    fn it_splits_the_cards_by_the_channel_of_their_board() {
+        let alice = TrelloUser("alice".to_string());
+        let ops_review = PendingReview {
+            board_id: "board2".to_string(),
+            ..PendingReview::for_test("ops", "Review", 1)
+        };
+        let other_review = PendingReview {
+            board_id: "board3".to_string(),
+            ..PendingReview::for_test("other", "Review", 1)
+        };
+        let cards_by_user = HashMap::from([(
+            alice.clone(),
+            vec![
+                PendingReview::for_test("team", "Review", 1),
+                ops_review,
+                other_review,
+            ],
+        )]);
+        let team = "C_TEAM".to_string();
+        let ops = "C_OPS".to_string();
+
+        let cards_by_channel = cards_by_channel(&cards_by_user, |board_id| match board_id {
+            "board1" => Some(&team),
+            "board2" => Some(&ops),
+            _ => None,
+        });
+
+        let card_ids: Vec<(&str, Vec<&str>)> = cards_by_channel
+            .iter()
+            .map(|(channel, cards_by_user)| {
+                let cards = cards_by_user[&alice]
+                    .iter()
+                    .map(|card| card.card_id.as_str());
+                (channel.as_str(), cards.collect())
+            })
+            .collect();
+        assert_eq!(card_ids, [("C_OPS", vec!["ops"]), ("C_TEAM", vec!["team"])]);
+    }
+}
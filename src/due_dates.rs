@@ -0,0 +1,438 @@
+use crate::{
+    ActionContext, BoardList, Recipient, TrelloUser, UserContext,
+    blocks::due_dates_blocks,
+    card_assignees,
+    config::{ActionConfig, DueDatesConfig, LabelConfig, MessageFormat, UnassignedConfig},
+    notify_recipients,
+    slack::Message,
+    state::{StateStore, TrackedCard},
+    template::{MessageTemplate, Templates},
+    trello::TrelloClient,
+};
+use anyhow::Result;
+use chrono_tz::Tz;
+use log::info;
+use serde::Serialize;
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+
+/// Section of a due dates reminder, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DueGroup {
+    Overdue,
+    Today,
+    /// Due after today, within the window
+    ThisWeek,
+}
+
+impl DueGroup {
+    /// Heading of the section in the message catalogs
+    pub fn message_id(self) -> &'static str {
+        match self {
+            DueGroup::Overdue => "due-dates-overdue",
+            DueGroup::Today => "due-dates-today",
+            DueGroup::ThisWeek => "due-dates-this-week",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DueCard {
+    pub card_id: String,
+    pub card_name: String,
+    pub card_url: String,
+    pub board_name: String,
+    pub list_id: String,
+    pub list_name: String,
+    pub due: OffsetDateTime,
+    /// Due date and time in the configured time zone, e.g. `2025-06-02 17:00`
+    pub local_due: String,
+    pub group: DueGroup,
+    /// Calendar days from today until the due date, negative if it is overdue since an earlier day
+    pub days_until_due: i64,
+    pub is_new: bool,
+    pub is_urgent: bool,
+}
+
+impl DueCard {
+    /// One siren for an overdue card, and another one for every day it is overdue
+    pub fn sirens(&self) -> usize {
+        match self.group {
+            DueGroup::Overdue => (1 - self.days_until_due.min(0)) as usize,
+            DueGroup::Today | DueGroup::ThisWeek => 0,
+        }
+    }
+}
+
+impl TrackedCard for DueCard {
+    fn card_id(&self) -> &str {
+        &self.card_id
+    }
+
+    fn list_id(&self) -> &str {
+        &self.list_id
+    }
+
+    fn set_new(&mut self, is_new: bool) {
+        self.is_new = is_new;
+    }
+
+    /// Overdue cards are reported on every run, like urgent ones
+    fn is_escalated(&self) -> bool {
+        self.group == DueGroup::Overdue || self.is_urgent
+    }
+}
+
+/// Current time and the window of the cards to report, with the time zone that determines the calendar days
+pub struct DueDateRange {
+    now: OffsetDateTime,
+    window: Duration,
+    timezone: Tz,
+}
+
+impl DueDateRange {
+    pub fn new(config: &DueDatesConfig, timezone: Tz) -> Self {
+        DueDateRange {
+            now: OffsetDateTime::now_utc(),
+            window: config.window.calendar_duration(),
+            timezone,
+        }
+    }
+
+    /// Section and calendar days until the due date, unless the card is due after the window
+    fn classify(&self, due: OffsetDateTime) -> Option<(DueGroup, i64)> {
+        if due > self.now + self.window {
+            return None;
+        }
+
+        let days_until_due =
+            (self.local(due).date_naive() - self.local(self.now).date_naive()).num_days();
+        let group = if due < self.now {
+            DueGroup::Overdue
+        } else if days_until_due == 0 {
+            DueGroup::Today
+        } else {
+            DueGroup::ThisWeek
+        };
+
+        Some((group, days_until_due))
+    }
+
+    fn format(&self, due: OffsetDateTime) -> String {
+        self.local(due).format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    fn local(&self, time: OffsetDateTime) -> chrono::DateTime<Tz> {
+        chrono::DateTime::from_timestamp(time.unix_timestamp(), 0)
+            .unwrap_or_default()
+            .with_timezone(&self.timezone)
+    }
+}
+
+/// ACTION: Send reminders of cards that are due soon, escalating overdue cards
+pub fn due_dates(
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = BoardList>,
+    range: &DueDateRange,
+) -> Result<()> {
+    let ActionContext {
+        trello_client,
+        slack_poster: _,
+        slack_directory: _,
+        calendar: _,
+        templates,
+        message_format,
+        snooze_buttons,
+        locale: _,
+        digest: _,
+        labels,
+        unassigned,
+        boards: _,
+    } = *context;
+
+    let due_cards = get_due_cards(
+        trello_client,
+        labels,
+        unassigned,
+        trello_member_id_to_username,
+        target_lists,
+        range,
+    )?;
+
+    let action = ActionConfig::DueDates.to_string();
+    let report = notify_recipients(
+        context,
+        state,
+        &action,
+        trello_to_slack_mapping,
+        due_cards,
+        "no-due-cards",
+        |recipient, trello_user, due_cards| {
+            compose_due_dates_message(
+                templates,
+                message_format,
+                snooze_buttons,
+                recipient,
+                trello_user,
+                due_cards,
+            )
+        },
+    )?;
+
+    report.finish()
+}
+
+/// Cards with an incomplete due date within the range per member or list owner
+fn get_due_cards(
+    trello_client: &TrelloClient,
+    labels: &LabelConfig,
+    unassigned: &UnassignedConfig,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = BoardList>,
+    range: &DueDateRange,
+) -> Result<HashMap<TrelloUser, Vec<DueCard>>> {
+    let mut due_cards = HashMap::<TrelloUser, Vec<DueCard>>::new();
+
+    for board_list in target_lists {
+        let BoardList {
+            board_name, list, ..
+        } = &board_list;
+        info!("Processing list '{}' (ID: {})", list.name, list.id);
+
+        let cards = trello_client.get_cards(&list.id)?;
+
+        for card in cards.iter().filter(|card| labels.includes(card)) {
+            let Some(due) = card.due.filter(|_| !card.due_complete) else {
+                continue;
+            };
+            let Some((group, days_until_due)) = range.classify(due) else {
+                continue; // not due soon
+            };
+
+            let trello_users =
+                card_assignees(card, &board_list, unassigned, trello_member_id_to_username);
+
+            if trello_users.is_empty() {
+                continue;
+            }
+
+            let due_card = DueCard {
+                card_id: card.id.clone(),
+                card_name: card.name.clone(),
+                card_url: card.url.clone(),
+                board_name: board_name.clone(),
+                list_id: list.id.clone(),
+                list_name: list.name.clone(),
+                due,
+                local_due: range.format(due),
+                group,
+                days_until_due,
+                is_new: false,
+                is_urgent: labels.is_urgent(card),
+            };
+            for trello_user in trello_users {
+                due_cards
+                    .entry(trello_user)
+                    .or_default()
+                    .push(due_card.clone());
+            }
+        }
+    }
+
+    Ok(due_cards)
+}
+
+/// Card with a due date as provided to the message template
+#[derive(Serialize)]
+struct DueCardContext {
+    card_id: String,
+    card_name: String,
+    card_url: String,
+    board_name: String,
+    list_name: String,
+    /// Due date and time in the configured time zone
+    due: String,
+    days_until_due: i64,
+    sirens: usize,
+    is_new: bool,
+    is_urgent: bool,
+}
+
+#[derive(Serialize)]
+struct DueGroupContext {
+    /// `overdue`, `today` or `this-week`
+    group: DueGroup,
+    cards: Vec<DueCardContext>,
+}
+
+#[derive(Serialize)]
+struct DueDatesContext<'a> {
+    user: UserContext<'a>,
+    count: usize,
+    groups: Vec<DueGroupContext>,
+}
+
+pub fn compose_due_dates_message(
+    templates: &Templates,
+    message_format: MessageFormat,
+    snooze_buttons: bool,
+    recipient: &Recipient,
+    trello_user: &TrelloUser,
+    mut due_cards: Vec<DueCard>,
+) -> Result<Message> {
+    due_cards.sort_by_key(|card| (card.group, card.due));
+
+    let blocks = (message_format == MessageFormat::Blocks).then(|| {
+        due_dates_blocks(
+            templates.translator(recipient.locale),
+            &due_cards,
+            snooze_buttons,
+        )
+    });
+
+    let count = due_cards.len();
+    let mut groups = Vec::<DueGroupContext>::new();
+    for card in due_cards {
+        let context = DueCardContext {
+            sirens: card.sirens(),
+            card_id: card.card_id,
+            card_name: card.card_name,
+            card_url: card.card_url,
+            board_name: card.board_name,
+            list_name: card.list_name,
+            due: card.local_due,
+            days_until_due: card.days_until_due,
+            is_new: card.is_new,
+            is_urgent: card.is_urgent,
+        };
+        match groups.last_mut() {
+            Some(group) if group.group == card.group => group.cards.push(context),
+            _ => groups.push(DueGroupContext {
+                group: card.group,
+                cards: vec![context],
+            }),
+        }
+    }
+
+    let text = templates.render(
+        MessageTemplate::DueDates,
+        recipient.locale,
+        DueDatesContext {
+            user: UserContext::new(recipient, trello_user),
+            count,
+            groups,
+        },
+    )?;
+
+    Ok(Message { text, blocks })
+}
+
+/// Cards of every section, e.g. to render the template with sample data
+pub fn sample_due_cards(range: &DueDateRange) -> Vec<DueCard> {
+    [-2, 0, 3]
+        .into_iter()
+        .enumerate()
+        .map(|(index, days)| {
+            let due = range.now + Duration::days(days) + Duration::hours(1);
+            let (group, days_until_due) = range.classify(due).unwrap_or((DueGroup::ThisWeek, days));
+            DueCard {
+                card_id: format!("sample{}", index + 1),
+                card_name: format!("Sample card {}", index + 1),
+                card_url: format!("https://trello.com/c/sample{}", index + 1),
+                board_name: "Sample board".to_string(),
+                list_id: "in-progress".to_string(),
+                list_name: "In Progress".to_string(),
+                due,
+                local_due: range.format(due),
+                group,
+                days_until_due,
+                is_new: index == 0,
+                is_urgent: false,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn range() -> DueDateRange {
+        DueDateRange {
+            now: datetime!(2025-06-04 10:00 UTC),
+            window: Duration::weeks(1),
+            timezone: chrono_tz::Europe::Berlin,
+        }
+    }
+
+    #[test]
+    fn it_groups_cards_by_calendar_day_in_the_time_zone() {
+        let range = range();
+
+        assert_eq!(
+            range.classify(datetime!(2025-06-02 15:00 UTC)),
+            Some((DueGroup::Overdue, -2))
+        );
+        assert_eq!(
+            range.classify(datetime!(2025-06-04 09:00 UTC)),
+            Some((DueGroup::Overdue, 0))
+        );
+        assert_eq!(
+            range.classify(datetime!(2025-06-04 21:00 UTC)),
+            Some((DueGroup::Today, 0))
+        );
+        // 22:00 UTC is already the next day in Berlin
+        assert_eq!(
+            range.classify(datetime!(2025-06-04 22:00 UTC)),
+            Some((DueGroup::ThisWeek, 1))
+        );
+        assert_eq!(
+            range.classify(datetime!(2025-06-11 10:00 UTC)),
+            Some((DueGroup::ThisWeek, 7))
+        );
+        assert_eq!(range.classify(datetime!(2025-06-11 10:01 UTC)), None);
+        assert_eq!(
+            range.format(datetime!(2025-06-04 21:00 UTC)),
+            "2025-06-04 23:00"
+        );
+    }
+
+    #[test]
+    fn it_renders_the_cards_grouped_by_due_date() {
+        let templates = Templates::for_test(None);
+        let recipient = Recipient::for_test();
+        let mut due_cards = sample_due_cards(&range());
+        due_cards.reverse();
+
+        let message = compose_due_dates_message(
+            &templates,
+            MessageFormat::Markdown,
+            false,
+            &recipient,
+            &TrelloUser("alice".to_string()),
+            due_cards,
+        )
+        .unwrap();
+
+        assert_eq!(
+            message.text,
+            "**⏰ You have 3 cards with an upcoming due date:**\n\
+            \n\
+            **🚨 Overdue**\n\
+            - [Sample card 1](https://trello.com/c/sample1) 🆕 - Due 2025-06-02 13:00 🚨🚨🚨\n\
+            \n\
+            **📅 Due today**\n\
+            - [Sample card 2](https://trello.com/c/sample2) - Due 2025-06-04 13:00 \n\
+            \n\
+            **🗓️ Due this week**\n\
+            - [Sample card 3](https://trello.com/c/sample3) - Due 2025-06-07 13:00 \n\
+            \n\n\n\
+            Please finish the cards in time or agree on a new due date!\n"
+        );
+    }
+}
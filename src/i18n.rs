@@ -0,0 +1,167 @@
+use fluent::{FluentArgs, FluentResource, FluentValue, concurrent::FluentBundle};
+use log::error;
+use unic_langid::{LanguageIdentifier, langid};
+
+/// Languages with a shipped message catalog
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    De,
+    En,
+}
+
+impl Locale {
+    fn catalog(self) -> &'static str {
+        match self {
+            Locale::De => include_str!("../locales/de.ftl"),
+            Locale::En => include_str!("../locales/en.ftl"),
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::De => write!(f, "de"),
+            Locale::En => write!(f, "en"),
+        }
+    }
+}
+
+/// Formats the messages of one locale, including its plural rules
+pub struct Translator {
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Translator {
+    pub fn new(locale: Locale) -> Self {
+        let resource = FluentResource::try_new(locale.catalog().to_string()).unwrap_or_else(
+            |(resource, errors)| {
+                error!("Message catalog for locale {locale} contains errors: {errors:?}");
+                resource
+            },
+        );
+
+        let language: LanguageIdentifier = match locale {
+            Locale::De => langid!("de"),
+            Locale::En => langid!("en"),
+        };
+        let mut bundle = FluentBundle::new_concurrent(vec![language]);
+        bundle.set_use_isolating(false); // Slack would show the Unicode isolation marks
+        if let Err(errors) = bundle.add_resource(resource) {
+            error!("Could not add message catalog for locale {locale}: {errors:?}");
+        }
+
+        Translator { bundle }
+    }
+
+    pub fn has_message(&self, id: &str) -> bool {
+        self.bundle.has_message(id)
+    }
+
+    /// Formats the message `id`, falling back to the `id` if it is missing
+    pub fn text(&self, id: &str, args: &[(&str, Arg)]) -> String {
+        let Some(pattern) = self
+            .bundle
+            .get_message(id)
+            .and_then(|message| message.value())
+        else {
+            error!("Missing message `{id}` in catalog");
+            return id.to_string();
+        };
+
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, FluentValue::from(value));
+        }
+
+        let mut errors = Vec::new();
+        let text = self
+            .bundle
+            .format_pattern(pattern, Some(&fluent_args), &mut errors);
+        if !errors.is_empty() {
+            error!("Could not format message `{id}`: {errors:?}");
+        }
+
+        text.into_owned()
+    }
+}
+
+/// Argument of a message, numbers select the plural form
+pub enum Arg {
+    Number(i64),
+    Text(String),
+}
+
+impl<'a> From<&'a Arg> for FluentValue<'a> {
+    fn from(arg: &'a Arg) -> Self {
+        match arg {
+            Arg::Number(number) => FluentValue::from(*number),
+            Arg::Text(text) => FluentValue::from(text.as_str()),
+        }
+    }
+}
+
+/// Translators of all shipped locales
+pub struct Catalog {
+    de: Translator,
+    en: Translator,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog {
+            de: Translator::new(Locale::De),
+            en: Translator::new(Locale::En),
+        }
+    }
+
+    pub fn translator(&self, locale: Locale) -> &Translator {
+        match locale {
+            Locale::De => &self.de,
+            Locale::En => &self.en,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_applies_plural_rules() {
+        let de = Translator::new(Locale::De);
+        let en = Translator::new(Locale::En);
+
+        assert_eq!(
+            de.text("pending-reviews-title", &[("count", Arg::Number(1))]),
+            "🔎 Du hast 1 ausstehendes Review:"
+        );
+        assert_eq!(
+            de.text("pending-reviews-title", &[("count", Arg::Number(3))]),
+            "🔎 Du hast 3 ausstehende Reviews:"
+        );
+        assert_eq!(
+            en.text("inactive-cards-title", &[("count", Arg::Number(1))]),
+            "📝 The following card has been in the sprint for a long time:"
+        );
+        assert_eq!(en.text("age-days", &[("value", Arg::Number(2))]), "2 days");
+    }
+
+    #[test]
+    fn it_ships_the_same_messages_for_all_locales() {
+        fn message_ids(locale: Locale) -> Vec<&'static str> {
+            let mut ids: Vec<&str> = locale
+                .catalog()
+                .lines()
+                .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+                .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+                .collect();
+            ids.sort_unstable();
+            ids
+        }
+
+        assert_eq!(message_ids(Locale::De), message_ids(Locale::En));
+    }
+}
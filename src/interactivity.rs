@@ -0,0 +1,500 @@
+use crate::{
+    SlackUser,
+    blocks::{ACKNOWLEDGE, CardReference, SNOOZE_DAY, SNOOZE_MONDAY},
+    config::AppConfig,
+    i18n::{Arg, Catalog, Locale, Translator},
+    slack::Message,
+    slash_commands::{SlashCommand, SlashCommandHandler},
+    state::{Snooze, StateStore},
+};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Datelike, TimeZone};
+use hmac::{Hmac, Mac};
+use log::{error, info, warn};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{Arc, Mutex, PoisonError, mpsc},
+};
+use time::OffsetDateTime;
+use tiny_http::{Request, Response, Server};
+
+const INTERACTIONS_PATH: &str = "/slack/interactions";
+const COMMANDS_PATH: &str = "/slack/commands";
+/// Requests with an older timestamp are rejected to prevent replay attacks
+const MAX_REQUEST_AGE_SECONDS: i64 = 5 * 60;
+/// Larger requests are rejected without reading them, Slack's payloads are far smaller
+const MAX_BODY_BYTES: u64 = 256 * 1024;
+
+/// Receives the clicks on the snooze buttons, recording the snoozes in the [`StateStore`],
+/// and answers the slash commands
+pub struct InteractivityServer {
+    signing_secret: String,
+    address: String,
+    timezone: chrono_tz::Tz,
+    client: reqwest::blocking::Client,
+    catalog: Arc<Catalog>,
+    default_locale: Locale,
+    locales: HashMap<SlackUser, Locale>,
+    commands: SlashCommandHandler,
+}
+
+/// Verified request to handle after it has been acknowledged
+enum Interaction {
+    BlockActions(BlockActions),
+    SlashCommand {
+        command: SlashCommand,
+        user: SlackUser,
+        response_url: String,
+    },
+}
+
+impl Interaction {
+    fn user(&self) -> &str {
+        match self {
+            Interaction::BlockActions(actions) => &actions.user.id,
+            Interaction::SlashCommand { user, .. } => &user.0,
+        }
+    }
+}
+
+/// Payload of a `block_actions` interaction, cf. <https://api.slack.com/reference/interaction-payloads/block-actions>
+#[derive(Debug, Deserialize)]
+struct BlockActions {
+    r#type: String,
+    user: InteractionUser,
+    actions: Vec<BlockAction>,
+    response_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InteractionUser {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockAction {
+    action_id: String,
+    value: Option<String>,
+}
+
+impl InteractivityServer {
+    /// Returns `None` if no signing secret is configured
+    pub fn new(
+        config: &AppConfig,
+        client: reqwest::blocking::Client,
+        catalog: Arc<Catalog>,
+    ) -> Result<Option<Self>> {
+        let Some(signing_secret) = config.interactivity.signing_secret.clone() else {
+            return Ok(None);
+        };
+        let commands = SlashCommandHandler::new(config, client.clone(), Arc::clone(&catalog))?;
+
+        Ok(Some(InteractivityServer {
+            signing_secret,
+            address: config.interactivity.address.clone(),
+            timezone: config.schedule.timezone,
+            client,
+            catalog,
+            default_locale: config.locale,
+            locales: config
+                .user_mapping
+                .iter()
+                .filter_map(|mapping| Some((mapping.slack_user.clone(), mapping.locale?)))
+                .collect(),
+            commands,
+        }))
+    }
+
+    /// Serves the endpoints in the background.
+    ///
+    /// Requests are acknowledged right away, since Slack expects a response within three seconds,
+    /// and handled afterwards, as the state may be locked by a running action.
+    pub fn spawn(self, state: Arc<Mutex<StateStore>>) -> Result<()> {
+        let server = Server::http(&self.address).map_err(|e| {
+            anyhow!(
+                "Could not start interactivity endpoint on {}: {e}",
+                self.address
+            )
+        })?;
+        info!(
+            "Listening for Slack interactions on http://{0}{INTERACTIONS_PATH} and slash commands on http://{0}{COMMANDS_PATH}",
+            self.address
+        );
+
+        let (sender, receiver) = mpsc::channel::<Interaction>();
+        let server_handler = Arc::new(self);
+        let worker_handler = Arc::clone(&server_handler);
+
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if let Some(interaction) = server_handler.respond(request) {
+                    // the worker only stops with the process
+                    let _ = sender.send(interaction);
+                }
+            }
+        });
+
+        std::thread::spawn(move || {
+            for interaction in receiver {
+                let result = match &interaction {
+                    Interaction::BlockActions(actions) => {
+                        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                        worker_handler.apply(&mut state, actions)
+                    }
+                    Interaction::SlashCommand {
+                        command,
+                        user,
+                        response_url,
+                    } => worker_handler.answer(&state, *command, user, response_url),
+                };
+                if let Err(e) = result {
+                    error!(
+                        "Failed to handle interaction of Slack user {}: {e:#}",
+                        interaction.user()
+                    );
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Verifies and acknowledges the request, returning the interaction to handle
+    fn respond(&self, mut request: Request) -> Option<Interaction> {
+        let result = self.parse(&mut request);
+        let status = match &result {
+            Ok(_) => 200,
+            Err(RequestError::NotFound) => 404,
+            Err(RequestError::PayloadTooLarge) => {
+                warn!("Rejected interaction request larger than {MAX_BODY_BYTES} bytes");
+                413
+            }
+            Err(RequestError::Unauthorized(e)) => {
+                warn!("Rejected interaction request: {e:#}");
+                401
+            }
+            Err(RequestError::BadRequest(e)) => {
+                warn!("Invalid interaction request: {e:#}");
+                400
+            }
+        };
+
+        if let Err(e) = request.respond(Response::empty(status)) {
+            error!("Failed to respond to interaction request: {e}");
+        }
+
+        result.ok().flatten()
+    }
+
+    fn parse(&self, request: &mut Request) -> Result<Option<Interaction>, RequestError> {
+        let path = request.url().split('?').next().unwrap_or_default();
+        if path != INTERACTIONS_PATH && path != COMMANDS_PATH {
+            return Err(RequestError::NotFound);
+        }
+        let is_command = path == COMMANDS_PATH;
+
+        if request
+            .body_length()
+            .is_some_and(|length| length as u64 > MAX_BODY_BYTES)
+        {
+            return Err(RequestError::PayloadTooLarge);
+        }
+        let body = read_body(request.as_reader())?;
+
+        let timestamp = header(request, "X-Slack-Request-Timestamp").unwrap_or_default();
+        let signature = header(request, "X-Slack-Signature").unwrap_or_default();
+        verify_signature(
+            &self.signing_secret,
+            &timestamp,
+            &body,
+            &signature,
+            OffsetDateTime::now_utc(),
+        )
+        .map_err(RequestError::Unauthorized)?;
+
+        let form: HashMap<String, String> = form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+        let field = |name: &str| {
+            form.get(name)
+                .cloned()
+                .ok_or_else(|| RequestError::BadRequest(anyhow!("Missing field {name}")))
+        };
+
+        if is_command {
+            let name = field("command")?;
+            let command = SlashCommand::from_name(&name)
+                .ok_or_else(|| RequestError::BadRequest(anyhow!("Unknown slash command {name}")))?;
+
+            return Ok(Some(Interaction::SlashCommand {
+                command,
+                user: SlackUser(field("user_id")?),
+                response_url: field("response_url")?,
+            }));
+        }
+
+        let interaction: BlockActions = serde_json::from_str(&field("payload")?)
+            .map_err(|e| RequestError::BadRequest(e.into()))?;
+
+        // other interactions, e.g. the "Open in Trello" link buttons, need no handling
+        Ok((interaction.r#type == "block_actions")
+            .then_some(Interaction::BlockActions(interaction)))
+    }
+
+    fn translator(&self, slack_user: &SlackUser) -> &Translator {
+        let locale = self
+            .locales
+            .get(slack_user)
+            .copied()
+            .unwrap_or(self.default_locale);
+        self.catalog.translator(locale)
+    }
+
+    /// Replies to a slash command with the cards of the user
+    fn answer(
+        &self,
+        state: &Mutex<StateStore>,
+        command: SlashCommand,
+        slack_user: &SlackUser,
+        response_url: &str,
+    ) -> Result<()> {
+        info!("Slack user {slack_user} queried {command:?}");
+
+        let message = self
+            .commands
+            .respond(command, slack_user, state)
+            .unwrap_or_else(|e| {
+                error!("Failed to answer {command:?} of Slack user {slack_user}: {e:#}");
+                Message {
+                    text: self.translator(slack_user).text("command-failed", &[]),
+                    blocks: None,
+                }
+            });
+
+        self.reply(response_url, &message)
+    }
+
+    fn apply(&self, state: &mut StateStore, interaction: &BlockActions) -> Result<()> {
+        let slack_user = SlackUser(interaction.user.id.clone());
+        let translator = self.translator(&slack_user);
+
+        for action in &interaction.actions {
+            let Some(value) = &action.value else {
+                continue;
+            };
+            let Ok(card) = serde_json::from_str::<CardReference>(value) else {
+                continue; // not one of the snooze buttons
+            };
+            let now = OffsetDateTime::now_utc();
+
+            let snooze = match action.action_id.as_str() {
+                SNOOZE_DAY => Snooze::Until {
+                    until: now + time::Duration::days(1),
+                },
+                SNOOZE_MONDAY => Snooze::Until {
+                    until: next_monday(now, self.timezone)?,
+                },
+                ACKNOWLEDGE => Snooze::Acknowledged {
+                    list_id: card.list_id,
+                },
+                _ => continue,
+            };
+
+            info!(
+                "Slack user {slack_user} snoozed card {}: {snooze:?}",
+                card.card_id
+            );
+
+            let confirmation = match &snooze {
+                Snooze::Until { until } => translator.text(
+                    "snoozed-until",
+                    &[("until", Arg::Text(self.format_time(*until)))],
+                ),
+                Snooze::Acknowledged { .. } => translator.text("acknowledged", &[]),
+            };
+
+            state.snooze(&slack_user, &card.card_id, snooze)?;
+
+            if let Some(response_url) = &interaction.response_url {
+                let confirmation = Message {
+                    text: confirmation,
+                    blocks: None,
+                };
+                self.reply(response_url, &confirmation)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends a message only visible to the user, without replacing the reminder the user interacted with
+    fn reply(&self, response_url: &str, message: &Message) -> Result<()> {
+        let mut body = serde_json::json!({
+            "response_type": "ephemeral",
+            "replace_original": false,
+            "text": message.text,
+        });
+        if let Some(blocks) = &message.blocks {
+            body["blocks"] = blocks.clone();
+        }
+
+        let response = self
+            .client
+            .post(response_url)
+            .json(&body)
+            .send()
+            .context("Failed to send reply")?;
+
+        if !response.status().is_success() {
+            bail!("Failed to send reply: {:?}", response.text()?);
+        }
+
+        Ok(())
+    }
+
+    fn format_time(&self, time: OffsetDateTime) -> String {
+        self.timezone
+            .timestamp_opt(time.unix_timestamp(), 0)
+            .single()
+            .map(|time| time.format("%d.%m.%Y %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+enum RequestError {
+    NotFound,
+    PayloadTooLarge,
+    Unauthorized(anyhow::Error),
+    BadRequest(anyhow::Error),
+}
+
+fn header(request: &Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+/// Reads at most [`MAX_BODY_BYTES`], since the length of chunked requests is unknown up front
+fn read_body(reader: impl Read) -> Result<String, RequestError> {
+    let mut body = String::new();
+    reader
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| RequestError::BadRequest(e.into()))?;
+
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(RequestError::PayloadTooLarge);
+    }
+    Ok(body)
+}
+
+/// Verifies Slack's request signature, cf. <https://api.slack.com/authentication/verifying-requests-from-slack>
+fn verify_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &str,
+    signature: &str,
+    now: OffsetDateTime,
+) -> Result<()> {
+    let request_time: i64 = timestamp.parse().context("Invalid request timestamp")?;
+    if (now.unix_timestamp() - request_time).abs() > MAX_REQUEST_AGE_SECONDS {
+        bail!("Request timestamp is too old");
+    }
+
+    let signature = signature
+        .strip_prefix("v0=")
+        .and_then(|signature| hex::decode(signature).ok())
+        .context("Invalid signature format")?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .context("Invalid signing secret")?;
+    mac.update(format!("v0:{timestamp}:{body}").as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| anyhow!("Signature mismatch"))
+}
+
+/// Start of the next Monday in the time zone, a week later if it is Monday already
+fn next_monday(now: OffsetDateTime, timezone: chrono_tz::Tz) -> Result<OffsetDateTime> {
+    let today = timezone
+        .timestamp_opt(now.unix_timestamp(), 0)
+        .single()
+        .context("Invalid time")?
+        .date_naive();
+    let days_until_monday = 7 - i64::from(today.weekday().num_days_from_monday());
+    let monday = today + chrono::Duration::days(days_until_monday);
+
+    let start = timezone
+        .from_local_datetime(&monday.and_time(chrono::NaiveTime::MIN))
+        .earliest()
+        .context("Monday midnight does not exist in the time zone")?;
+
+    OffsetDateTime::from_unix_timestamp(start.timestamp()).context("Invalid time")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{timestamp}:{body}").as_bytes());
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn it_verifies_request_signatures() {
+        let now = datetime!(2025-11-24 09:00:00 +00:00);
+        let timestamp = now.unix_timestamp().to_string();
+        let body = "payload=%7B%7D";
+        let signature = sign("secret", &timestamp, body);
+
+        assert!(verify_signature("secret", &timestamp, body, &signature, now).is_ok());
+        assert!(verify_signature("other", &timestamp, body, &signature, now).is_err());
+        assert!(verify_signature("secret", &timestamp, "payload=", &signature, now).is_err());
+        assert!(
+            verify_signature(
+                "secret",
+                &timestamp,
+                body,
+                &signature,
+                now + time::Duration::minutes(10)
+            )
+            .is_err(),
+            "replayed requests are rejected"
+        );
+    }
+
+    #[test]
+    fn it_limits_the_request_body() {
+        let payload = "payload=".to_string() + &"x".repeat(100);
+        assert!(matches!(read_body(payload.as_bytes()), Ok(body) if body == payload));
+
+        let oversized = "x".repeat(usize::try_from(MAX_BODY_BYTES).unwrap() + 1);
+        assert!(matches!(
+            read_body(oversized.as_bytes()),
+            Err(RequestError::PayloadTooLarge)
+        ));
+    }
+
+    #[test]
+    fn it_snoozes_until_monday() {
+        let berlin = chrono_tz::Europe::Berlin;
+
+        // Friday evening
+        assert_eq!(
+            next_monday(datetime!(2025-11-21 18:00:00 +00:00), berlin).unwrap(),
+            datetime!(2025-11-23 23:00:00 +00:00)
+        );
+        // Monday morning
+        assert_eq!(
+            next_monday(datetime!(2025-11-24 08:00:00 +00:00), berlin).unwrap(),
+            datetime!(2025-11-30 23:00:00 +00:00)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use crate::BoardList;
+use regex::Regex;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Selects Trello lists by ID, name, glob or regex pattern, optionally restricted to a board.
+///
+/// Examples: `Review`, `Review*`, `/^Review \d+$/`, `5f1d...` (a 24 digit list ID),
+/// and `Team Board/Review` to only select the list of the board with the name or ID `Team Board`.
+/// Slashes in names are escaped with a backslash, e.g. `Review\/QA` selects the list `Review/QA`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ListSelector {
+    raw: String,
+    board: Option<String>,
+    pattern: ListPattern,
+}
+
+#[derive(Clone, Debug)]
+enum ListPattern {
+    Id(String),
+    Name(String),
+    /// A glob or regex pattern
+    Pattern(Regex),
+}
+
+impl ListSelector {
+    /// Whether the selector is not restricted to another board
+    pub fn applies_to(&self, board_id: &str, board_name: &str) -> bool {
+        self.board
+            .as_deref()
+            .is_none_or(|board| board == board_id || board == board_name)
+    }
+
+    pub fn matches(&self, board_list: &BoardList) -> bool {
+        if !self.applies_to(&board_list.board_id, &board_list.board_name) {
+            return false;
+        }
+
+        match &self.pattern {
+            ListPattern::Id(id) => board_list.list.id == *id,
+            ListPattern::Name(name) => board_list.list.name == *name,
+            ListPattern::Pattern(regex) => regex.is_match(&board_list.list.name),
+        }
+    }
+
+    /// The board the selector is restricted to, if any
+    pub fn board(&self) -> Option<&str> {
+        self.board.as_deref()
+    }
+
+    /// List name of selectors that are no ID or pattern
+    pub fn name(&self) -> Option<&str> {
+        match &self.pattern {
+            ListPattern::Name(name) => Some(name),
+            ListPattern::Id(_) | ListPattern::Pattern(_) => None,
+        }
+    }
+
+    /// Whether the selector is meant to match several lists
+    pub fn is_pattern(&self) -> bool {
+        matches!(self.pattern, ListPattern::Pattern(_))
+    }
+}
+
+impl FromStr for ListSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim();
+
+        // a regex may contain slashes, so it is only qualified by a board if it does not start the selector
+        let (board, list) = match board_delimiter(raw) {
+            Some(index) if index > 0 => (Some(raw[..index].trim()), raw[index + 1..].trim()),
+            _ => (None, raw),
+        };
+        if list.is_empty() || board.is_some_and(str::is_empty) {
+            return Err(format!("Invalid list selector: {s}"));
+        }
+        let board = board.map(unescape);
+
+        let invalid_pattern =
+            |e: regex::Error| format!("Invalid pattern in list selector {s}: {e}");
+        let pattern = if let Some(regex) = list
+            .strip_prefix('/')
+            .and_then(|list| list.strip_suffix('/'))
+        {
+            ListPattern::Pattern(Regex::new(regex).map_err(invalid_pattern)?)
+        } else if list.len() == 24 && list.chars().all(|c| c.is_ascii_hexdigit()) {
+            ListPattern::Id(list.to_string())
+        } else if list.contains(['*', '?']) {
+            ListPattern::Pattern(glob_to_regex(&unescape(list)).map_err(invalid_pattern)?)
+        } else {
+            ListPattern::Name(unescape(list))
+        };
+
+        Ok(ListSelector {
+            raw: raw.to_string(),
+            board,
+            pattern,
+        })
+    }
+}
+
+/// Index of the first slash that is not escaped with a backslash
+fn board_delimiter(selector: &str) -> Option<usize> {
+    selector
+        .char_indices()
+        .find(|&(index, c)| c == '/' && !selector[..index].ends_with('\\'))
+        .map(|(index, _)| index)
+}
+
+fn unescape(name: &str) -> String {
+    name.replace("\\/", "/")
+}
+
+/// Regex of a glob pattern, where `*` matches any text and `?` a single character
+fn glob_to_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let mut regex = String::from("^");
+    let mut literal = String::new();
+    for c in glob.chars() {
+        let wildcard = match c {
+            '*' => ".*",
+            '?' => ".",
+            _ => {
+                literal.push(c);
+                continue;
+            }
+        };
+        regex.push_str(&regex::escape(&literal));
+        regex.push_str(wildcard);
+        literal.clear();
+    }
+    regex.push_str(&regex::escape(&literal));
+    regex.push('$');
+
+    Regex::new(&regex)
+}
+
+impl TryFrom<String> for ListSelector {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for ListSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::List;
+
+    fn board_list(board_id: &str, board_name: &str, list_id: &str, list_name: &str) -> BoardList {
+        BoardList {
+            board_id: board_id.to_string(),
+            board_name: board_name.to_string(),
+            list: List {
+                id: list_id.to_string(),
+                name: list_name.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn it_matches_lists_by_id_name_and_pattern() {
+        let review = board_list("b1", "Team", "5f1d7c3e9a2b4c0012345678", "Review");
+        let review_2 = board_list("b1", "Team", "5f1d7c3e9a2b4c0012345679", "Review 2");
+        let other_review = board_list("b2", "Ops", "5f1d7c3e9a2b4c001234567a", "Review");
+
+        let selector = |s: &str| s.parse::<ListSelector>().unwrap();
+
+        assert!(selector("Review").matches(&review));
+        assert!(!selector("Review").matches(&review_2));
+        assert!(selector("5f1d7c3e9a2b4c0012345678").matches(&review));
+        assert!(!selector("5f1d7c3e9a2b4c0012345678").matches(&other_review));
+        assert!(selector("Review*").matches(&review_2));
+        assert!(selector("Review ?").matches(&review_2));
+        assert!(!selector("Rev?").matches(&review));
+        assert!(selector(r"/^Review \d$/").matches(&review_2));
+        assert!(!selector(r"/^Review \d$/").matches(&review));
+
+        assert!(selector("Team/Review").matches(&review));
+        assert!(selector("b2/Review").matches(&other_review));
+        assert!(!selector("Team/Review").matches(&other_review));
+        assert!(selector("Ops//^Rev/").matches(&other_review));
+        assert!(selector("Ops//^Rev/").is_pattern());
+    }
+
+    #[test]
+    fn it_matches_list_names_with_escaped_slashes() {
+        let review_qa = board_list("b1", "Team", "5f1d7c3e9a2b4c0012345678", "Review/QA");
+        let doing = board_list("b1", "Team", "5f1d7c3e9a2b4c0012345679", "To Do / Doing");
+        let ops_doing = board_list("b2", "Dev/Ops", "5f1d7c3e9a2b4c001234567a", "To Do / Doing");
+
+        let selector = |s: &str| s.parse::<ListSelector>().unwrap();
+
+        assert!(selector(r"Review\/QA").matches(&review_qa));
+        assert_eq!(selector(r"Review\/QA").board(), None);
+        assert_eq!(selector(r"Review\/QA").name(), Some("Review/QA"));
+        assert!(selector(r"Review\/*").matches(&review_qa));
+        assert!(selector(r"To Do \/ Doing").matches(&doing));
+        assert!(selector(r"To Do \/ Doing").matches(&ops_doing));
+        assert!(selector(r"Team/To Do \/ Doing").matches(&doing));
+        assert!(!selector(r"Team/To Do \/ Doing").matches(&ops_doing));
+        assert!(selector(r"Dev\/Ops/To Do \/ Doing").matches(&ops_doing));
+
+        // without escaping, the text before the slash is a board
+        assert_eq!(selector("Review/QA").board(), Some("Review"));
+        assert!(!selector("Review/QA").matches(&review_qa));
+    }
+
+    #[test]
+    fn it_rejects_invalid_selectors() {
+        assert!("/(/".parse::<ListSelector>().is_err());
+        assert!("Team/".parse::<ListSelector>().is_err());
+        assert!("".parse::<ListSelector>().is_err());
+    }
+}
@@ -1,31 +1,114 @@
 use crate::{
-    config::{ActionConfig, AppConfig},
-    schema::List,
-    slack::SlackMessagePoster,
+    blocked_cards::{blocked_cards, compose_blocked_cards_message},
+    blocks::{inactive_cards_blocks, pending_reviews_blocks},
+    calendar::WorkCalendar,
+    check::check_config,
+    config::{
+        ActionConfig, AppConfig, BoardConfig, DigestConfig, LabelConfig, MessageFormat, Threshold,
+        UnassignedConfig, UpdatePolicy,
+    },
+    digest::{compose_digests, compose_inactive_cards_digest, compose_pending_reviews_digest},
+    due_dates::{DueDateRange, compose_due_dates_message, due_dates, sample_due_cards},
+    i18n::{Arg, Catalog, Locale, Translator},
+    interactivity::InteractivityServer,
+    metrics::metrics,
+    retry::RetryPolicy,
+    scheduler::run_schedule,
+    schema::{Card, List, Member},
+    slack::{DeliveryReport, Message, SlackDirectory, SlackMessagePoster},
+    state::{StateStore, TrackedCard},
+    template::{MessageTemplate, Templates},
     trello::{TrelloClient, last_update_from_card, moved_to_list_date},
+    unassigned_cards::{
+        compose_unassigned_cards_message, sample_unassigned_lists, unassigned_cards,
+    },
+    user_mapping::UserMappingTable,
     util::{print_summary, setup_tracing},
+    wip_limits::{ListLimits, compose_wip_limits_message, sample_wip_violations, wip_limits},
 };
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result, bail};
 use log::{error, info};
+use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
-    fmt::{Display, Write},
+    fmt::Display,
     hash::Hash,
+    io::Write,
+    sync::{Arc, Mutex, PoisonError},
 };
-use time::OffsetDateTime;
+use time::Duration;
 
+mod blocked_cards;
+mod blocks;
+mod calendar;
+mod check;
 mod config;
+mod digest;
+mod due_dates;
+mod i18n;
+mod interactivity;
+mod list_selector;
+mod metrics;
+mod retry;
+mod scheduler;
 mod schema;
 mod slack;
+mod slash_commands;
+mod state;
+mod template;
 mod trello;
+mod unassigned_cards;
+mod user_mapping;
 mod util;
+mod wip_limits;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TrelloUser(pub String);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SlackUser(pub String);
 
+/// Slack user to notify and the language to notify them in
+struct Recipient {
+    slack_user: SlackUser,
+    locale: Locale,
+}
+
+#[cfg(test)]
+impl Recipient {
+    /// An English recipient, e.g. to compose messages
+    fn for_test() -> Self {
+        Recipient {
+            slack_user: SlackUser("U1".to_string()),
+            locale: Locale::En,
+        }
+    }
+}
+
+/// Trello list together with the board it belongs to
+struct BoardList {
+    board_id: String,
+    board_name: String,
+    list: List,
+}
+
+/// Clients and settings shared by all actions
+struct ActionContext<'a> {
+    trello_client: &'a TrelloClient,
+    slack_poster: &'a SlackMessagePoster,
+    slack_directory: &'a SlackDirectory,
+    calendar: &'a WorkCalendar,
+    templates: &'a Templates,
+    message_format: MessageFormat,
+    snooze_buttons: bool,
+    /// Language of the team digests
+    locale: Locale,
+    digest: &'a DigestConfig,
+    labels: &'a LabelConfig,
+    unassigned: &'a UnassignedConfig,
+    /// Per-board settings, e.g. the channels of the boards
+    boards: &'a [BoardConfig],
+}
+
 impl Display for TrelloUser {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -42,7 +125,7 @@ fn main() -> Result<()> {
     setup_tracing();
     dotenvy::dotenv().ok(); // load .env file
 
-    let config = AppConfig::parse();
+    let config = AppConfig::load()?;
 
     print_summary(&config);
 
@@ -51,157 +134,726 @@ fn main() -> Result<()> {
         config.action
     );
 
-    let trello_to_slack_mapping: HashMap<TrelloUser, SlackUser> = config
-        .user_mapping
-        .iter()
-        .map(|mapping| (mapping.trello_user.clone(), mapping.slack_user.clone()))
-        .collect();
+    let calendar = WorkCalendar::from_config(&config.calendar, config.schedule.timezone)?;
+    let catalog = Arc::new(Catalog::new());
+    let templates = Templates::new(&config.templates, Arc::clone(&catalog))?;
+
+    if let ActionConfig::RenderTemplate { template } = &config.action {
+        return render_template_sample(&config, &calendar, &templates, *template);
+    }
 
     let request_client = reqwest::blocking::Client::new();
-    let trello_client = TrelloClient::new(request_client.clone(), &config.trello);
+    let retry = RetryPolicy::new(&config.retry);
+    let trello_client = TrelloClient::new(request_client.clone(), retry.clone(), &config.trello);
+    let slack_poster =
+        SlackMessagePoster::new(request_client.clone(), retry.clone(), &config.slack)?;
+    let slack_directory = SlackDirectory::new(request_client.clone(), retry, &config.slack);
 
-    let mut members = HashSet::new();
-    for board_id in &config.trello.board_ids {
-        let board_members = trello_client.get_members(board_id)?;
-        members.extend(board_members);
+    match &config.action {
+        ActionConfig::Mapping => {
+            return print_user_mapping(&config, &trello_client, &slack_directory);
+        }
+        ActionConfig::Check => return check(&config, &trello_client, &slack_directory),
+        ActionConfig::Metrics(metrics_config) => {
+            return metrics(&config, metrics_config, &trello_client, &calendar);
+        }
+        _ => {}
     }
 
-    info!("Fetched {} unique Trello members", members.len());
+    let mut state = StateStore::load(&config.state)?;
+    if config.slack.dry_run {
+        state = state.read_only();
+    }
 
-    let trello_member_id_to_username: HashMap<String, TrelloUser> = members
-        .into_iter()
-        .map(|member| (member.id.clone(), TrelloUser(member.username.clone())))
-        .collect();
+    let context = ActionContext {
+        trello_client: &trello_client,
+        slack_poster: &slack_poster,
+        slack_directory: &slack_directory,
+        calendar: &calendar,
+        templates: &templates,
+        message_format: config.slack.message_format,
+        // only the serve command receives the clicks
+        snooze_buttons: matches!(config.action, ActionConfig::Serve)
+            && config.interactivity.signing_secret.is_some(),
+        locale: config.locale,
+        digest: &config.digest,
+        labels: &config.labels,
+        unassigned: &config.unassigned,
+        boards: &config.boards,
+    };
 
-    let mut lists = Vec::new();
-    for board in &config.trello.board_ids {
-        let board_lists = trello_client.get_lists(board)?;
+    match &config.action {
+        ActionConfig::Serve => {
+            let state = Arc::new(Mutex::new(state));
+            if let Some(server) = InteractivityServer::new(&config, request_client, catalog)? {
+                server.spawn(Arc::clone(&state))?;
+            }
 
-        lists.extend(board_lists);
+            run_schedule(&config.schedule, |action| {
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                run_action(&config, action, &context, &mut state)
+            })
+        }
+        action => run_action(&config, action, &context, &mut state),
     }
+}
 
-    info!("Fetched {} lists from boards", lists.len());
-
-    let slack_poster = SlackMessagePoster::new(request_client.clone(), &config.slack);
+/// Fetches the current board data and performs a single action
+fn run_action(
+    config: &AppConfig,
+    action: &ActionConfig,
+    context: &ActionContext,
+    state: &mut StateStore,
+) -> Result<()> {
+    let Boards {
+        members,
+        trello_member_id_to_username,
+        lists,
+    } = fetch_boards(config, context.trello_client)?;
+    let user_mapping = UserMappingTable::resolve(config, &members, context.slack_directory)?;
+    let trello_to_slack_mapping = recipients(config, &user_mapping);
 
-    match config.action {
+    match action {
         ActionConfig::PendingReviews => {
-            if config.trello.review_lists.is_empty() {
+            if config
+                .trello
+                .board_ids
+                .iter()
+                .all(|board_id| config.review_lists(board_id).is_empty())
+            {
                 error!("No review lists configured, cannot proceed with pending reviews action");
                 return Ok(());
             }
             pending_reviews(
-                &trello_client,
-                &slack_poster,
+                context,
+                state,
                 &trello_to_slack_mapping,
                 &trello_member_id_to_username,
-                lists
-                    .into_iter()
-                    .filter(|list| config.trello.review_lists.contains(&list.name)),
+                review_lists(config, lists),
             )
         }
         ActionConfig::InactiveCards => {
-            if config.trello.inactive_cards_lists.is_empty() {
+            if config
+                .trello
+                .board_ids
+                .iter()
+                .all(|board_id| config.inactive_cards_lists(board_id).is_empty())
+            {
                 error!(
                     "No inactive cards lists configured, cannot proceed with inactive cards action"
                 );
                 return Ok(());
             }
             inactive_cards(
-                &trello_client,
-                &slack_poster,
+                context,
+                state,
+                &trello_to_slack_mapping,
+                &trello_member_id_to_username,
+                inactive_cards_lists(config, lists),
+            )
+        }
+        ActionConfig::DueDates => due_dates(
+            context,
+            state,
+            &trello_to_slack_mapping,
+            &trello_member_id_to_username,
+            due_dates_lists(config, lists),
+            &DueDateRange::new(&config.due_dates, config.schedule.timezone),
+        ),
+        ActionConfig::BlockedCards => {
+            if config.labels.blocked.is_empty() {
+                error!("No blocked labels configured, cannot proceed with blocked cards action");
+                return Ok(());
+            }
+            blocked_cards(
+                context,
+                state,
+                &trello_to_slack_mapping,
+                &trello_member_id_to_username,
+                watched_lists(config, lists),
+            )
+        }
+        ActionConfig::WipLimits => {
+            if config.wip_limits.lists.is_empty() && config.wip_limits.members.is_empty() {
+                error!("No WIP limits configured, cannot proceed with WIP limits action");
+                return Ok(());
+            }
+            wip_limits(
+                context,
+                state,
                 &trello_to_slack_mapping,
                 &trello_member_id_to_username,
-                lists
-                    .into_iter()
-                    .filter(|list| config.trello.inactive_cards_lists.contains(&list.name)),
+                wip_limit_lists(config, lists),
+                config.wip_limits.channel.as_ref(),
             )
         }
+        ActionConfig::UnassignedCards => unassigned_cards(
+            context,
+            state,
+            &trello_to_slack_mapping,
+            &trello_member_id_to_username,
+            watched_lists(config, lists),
+            config.unassigned.channel.as_ref(),
+        ),
+        ActionConfig::Serve
+        | ActionConfig::RenderTemplate { .. }
+        | ActionConfig::Mapping
+        | ActionConfig::Check
+        | ActionConfig::Metrics(_) => {
+            bail!("The {action} command cannot be run as a single action")
+        }
+    }
+}
+
+/// Recipients of the notifications by Trello user
+fn recipients(
+    config: &AppConfig,
+    user_mapping: &UserMappingTable,
+) -> HashMap<TrelloUser, Recipient> {
+    user_mapping
+        .user_mappings()
+        .map(|mapping| {
+            let recipient = Recipient {
+                slack_user: mapping.slack_user.clone(),
+                locale: mapping.locale.unwrap_or(config.locale),
+            };
+            (mapping.trello_user.clone(), recipient)
+        })
+        .collect()
+}
+
+/// Members and lists of the configured boards
+struct Boards {
+    members: Vec<Member>,
+    trello_member_id_to_username: HashMap<String, TrelloUser>,
+    lists: Vec<BoardList>,
+}
+
+/// Members of all configured boards, without duplicates
+fn fetch_members(config: &AppConfig, trello_client: &TrelloClient) -> Result<Vec<Member>> {
+    let mut members = HashSet::new();
+    for board_id in &config.trello.board_ids {
+        let board_members = trello_client.get_members(board_id)?;
+        members.extend(board_members);
+    }
+
+    info!("Fetched {} unique Trello members", members.len());
+
+    Ok(members.into_iter().collect())
+}
+
+fn fetch_boards(config: &AppConfig, trello_client: &TrelloClient) -> Result<Boards> {
+    let members = fetch_members(config, trello_client)?;
+
+    let trello_member_id_to_username: HashMap<String, TrelloUser> = members
+        .iter()
+        .map(|member| (member.id.clone(), TrelloUser(member.username.clone())))
+        .collect();
+
+    let mut lists = Vec::new();
+    for board_id in &config.trello.board_ids {
+        let board = trello_client.get_board(board_id)?;
+        let board_lists = trello_client.get_lists(board_id)?;
+
+        lists.extend(board_lists.into_iter().map(|list| BoardList {
+            board_id: board_id.clone(),
+            board_name: board.name.clone(),
+            list,
+        }));
     }
+
+    info!("Fetched {} lists from boards", lists.len());
+
+    Ok(Boards {
+        members,
+        trello_member_id_to_username,
+        lists,
+    })
+}
+
+/// Prints the user mapping as resolved for the actions, to check the automatic mapping
+fn print_user_mapping(
+    config: &AppConfig,
+    trello_client: &TrelloClient,
+    slack_directory: &SlackDirectory,
+) -> Result<()> {
+    let members = fetch_members(config, trello_client)?;
+    let user_mapping = UserMappingTable::resolve(config, &members, slack_directory)?;
+
+    std::io::stdout()
+        .write_all(user_mapping.render().as_bytes())
+        .context("Could not write the user mapping")
+}
+
+/// Prints the problems of the configuration, failing if there are any
+fn check(
+    config: &AppConfig,
+    trello_client: &TrelloClient,
+    slack_directory: &SlackDirectory,
+) -> Result<()> {
+    let report = check_config(config, trello_client, slack_directory)?;
+
+    std::io::stdout()
+        .write_all(report.render().as_bytes())
+        .context("Could not write the check report")?;
+
+    if !report.problems.is_empty() {
+        bail!(
+            "Found {} problems in the configuration",
+            report.problems.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// The lists configured as review lists of their board
+fn review_lists(config: &AppConfig, lists: Vec<BoardList>) -> impl Iterator<Item = BoardList> {
+    lists.into_iter().filter(|board_list| {
+        config
+            .review_lists(&board_list.board_id)
+            .iter()
+            .any(|selector| selector.matches(board_list))
+    })
+}
+
+/// The lists configured as inactive cards lists of their board, together with their threshold
+fn inactive_cards_lists(
+    config: &AppConfig,
+    lists: Vec<BoardList>,
+) -> impl Iterator<Item = (BoardList, Threshold)> {
+    lists
+        .into_iter()
+        .filter(|board_list| {
+            config
+                .inactive_cards_lists(&board_list.board_id)
+                .iter()
+                .any(|selector| selector.matches(board_list))
+        })
+        .map(|board_list| {
+            let threshold = config.inactive_threshold(&board_list);
+            (board_list, threshold)
+        })
+}
+
+/// The lists configured for due dates, or all lists if none are configured
+fn due_dates_lists(config: &AppConfig, lists: Vec<BoardList>) -> impl Iterator<Item = BoardList> {
+    lists.into_iter().filter(|board_list| {
+        config.due_dates.lists.is_empty()
+            || config
+                .due_dates
+                .lists
+                .iter()
+                .any(|selector| selector.matches(board_list))
+    })
+}
+
+/// The review and inactive cards lists of their board, together with their inactivity threshold
+fn watched_lists(
+    config: &AppConfig,
+    lists: Vec<BoardList>,
+) -> impl Iterator<Item = (BoardList, Threshold)> {
+    lists
+        .into_iter()
+        .filter(|board_list| {
+            config
+                .review_lists(&board_list.board_id)
+                .iter()
+                .chain(config.inactive_cards_lists(&board_list.board_id))
+                .any(|selector| selector.matches(board_list))
+        })
+        .map(|board_list| {
+            let threshold = config.inactive_threshold(&board_list);
+            (board_list, threshold)
+        })
+}
+
+/// The lists with a WIP limit, together with their limits
+fn wip_limit_lists(
+    config: &AppConfig,
+    lists: Vec<BoardList>,
+) -> impl Iterator<Item = (BoardList, ListLimits)> {
+    lists.into_iter().filter_map(|board_list| {
+        let limits = ListLimits {
+            list: config.wip_limits.list_limit(&board_list),
+            member: config.wip_limits.member_limit(&board_list),
+        };
+        (limits.list.is_some() || limits.member.is_some()).then_some((board_list, limits))
+    })
 }
 
 /// ACTION: Send notifications for pending reviews
 fn pending_reviews(
-    trello_client: &TrelloClient,
-    slack_poster: &SlackMessagePoster,
-    trello_to_slack_mapping: &HashMap<TrelloUser, SlackUser>,
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
     trello_member_id_to_username: &HashMap<String, TrelloUser>,
-    target_lists: impl Iterator<Item = List>,
+    target_lists: impl Iterator<Item = BoardList>,
 ) -> Result<()> {
-    let pending_reviews =
-        get_pending_reviews(trello_client, trello_member_id_to_username, target_lists)?;
+    let ActionContext {
+        trello_client,
+        slack_poster,
+        slack_directory: _,
+        calendar,
+        templates,
+        message_format,
+        snooze_buttons,
+        locale,
+        digest,
+        labels,
+        unassigned,
+        boards,
+    } = *context;
+
+    let pending_reviews = get_pending_reviews(
+        trello_client,
+        calendar,
+        labels,
+        unassigned,
+        trello_member_id_to_username,
+        target_lists,
+    )?;
+
+    let digests = compose_digests(
+        &pending_reviews,
+        |board_id| digest.pending_reviews_channel_for(boards, board_id),
+        |pending_reviews| {
+            let recipients = trello_to_slack_mapping;
+            compose_pending_reviews_digest(templates, locale, pending_reviews, recipients)
+        },
+    )?;
+
+    let action = ActionConfig::PendingReviews.to_string();
+    let mut report = notify_recipients(
+        context,
+        state,
+        &action,
+        trello_to_slack_mapping,
+        pending_reviews,
+        "no-pending-reviews",
+        |recipient, trello_user, pending_reviews| {
+            compose_pending_reviews_message(
+                templates,
+                message_format,
+                snooze_buttons,
+                recipient,
+                trello_user,
+                pending_reviews,
+            )
+        },
+    )?;
+    post_digest(slack_poster, state, &mut report, &action, digests)?;
+
+    report.finish()
+}
+
+/// Reminds the mapped users of their cards, leaving out snoozed cards and cards reminded of recently.
+/// The previous reminders of users without any cards left are retired with the text `no_cards_text_id`.
+///
+/// Returns the report of the deliveries, e.g. to add the digests.
+fn notify_recipients<C: TrackedCard>(
+    context: &ActionContext,
+    state: &mut StateStore,
+    action: &str,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    cards_by_user: HashMap<TrelloUser, Vec<C>>,
+    no_cards_text_id: &str,
+    compose: impl Fn(&Recipient, &TrelloUser, Vec<C>) -> Result<Message>,
+) -> Result<DeliveryReport> {
+    let mut report = DeliveryReport::default();
+    let mut users_with_cards = HashSet::new();
+    let mut cards_by_user = cards_by_user;
+
+    for (slack_user, mappings) in
+        trello_users_by_slack_user(trello_to_slack_mapping, &cards_by_user)
+    {
+        // one reminder per Slack user, addressed like the first of their Trello users
+        let (trello_user, recipient) = mappings[0];
+        let trello_users: Vec<&TrelloUser> = mappings.iter().map(|(user, _)| *user).collect();
+        let cards = cards_of(&mut cards_by_user, &trello_users);
+
+        let card_ids = card_ids_of(&cards);
 
-    for (trello_user, pending_reviews) in pending_reviews {
-        if pending_reviews.is_empty() {
+        let cards = state.skip_snoozed(slack_user, cards);
+        if !cards.is_empty() {
+            users_with_cards.insert(slack_user.clone());
+        }
+        let cards = state.select(context.calendar, action, slack_user, cards);
+        if cards.is_empty() {
+            info!("No cards to remind Slack user {slack_user} of, skipping {action} notification");
+            state.record(action, slack_user, card_ids.iter().map(String::as_str), [])?;
             continue;
         }
-        let Some(slack_user) = trello_to_slack_mapping.get(&trello_user) else {
+
+        let reported_card_ids = card_ids_of(&cards);
+
+        info!(
+            "Sending {action} notification to Slack user {slack_user} for Trello user {trello_user}"
+        );
+
+        let message = compose(recipient, trello_user, cards)?;
+        if !send_reminder(
+            context.slack_poster,
+            state,
+            &mut report,
+            action,
+            slack_user,
+            trello_user,
+            &message,
+        )? {
+            continue;
+        }
+
+        state.record(
+            action,
+            slack_user,
+            card_ids.iter().map(String::as_str),
+            reported_card_ids.iter().map(String::as_str),
+        )?;
+    }
+
+    retire_messages(
+        context,
+        state,
+        action,
+        trello_to_slack_mapping,
+        &users_with_cards,
+        no_cards_text_id,
+    )?;
+
+    Ok(report)
+}
+
+/// Sends a reminder to the user, replacing the previous reminder of the action.
+///
+/// Returns whether the reminder was delivered.
+fn send_reminder(
+    slack_poster: &SlackMessagePoster,
+    state: &mut StateStore,
+    report: &mut DeliveryReport,
+    action: &str,
+    slack_user: &SlackUser,
+    trello_user: &TrelloUser,
+    message: &Message,
+) -> Result<bool> {
+    let previous = state.message(action, &slack_user.0);
+    let result = slack_poster.post_message(slack_user, trello_user, message, previous);
+    let Some(posted) = report.record(slack_user, trello_user, result) else {
+        return Ok(false);
+    };
+
+    state.remember_message(action, &slack_user.0, posted)?;
+    Ok(true)
+}
+
+/// Mapped Trello users with cards per Slack user, sorted by name, as several Trello users may belong to one Slack user
+fn trello_users_by_slack_user<'a, C>(
+    trello_to_slack_mapping: &'a HashMap<TrelloUser, Recipient>,
+    cards_by_user: &HashMap<TrelloUser, Vec<C>>,
+) -> HashMap<&'a SlackUser, Vec<(&'a TrelloUser, &'a Recipient)>> {
+    let mut trello_users_by_slack_user = HashMap::<&SlackUser, Vec<_>>::new();
+    for (trello_user, cards) in cards_by_user {
+        if cards.is_empty() {
+            continue;
+        }
+        let Some((trello_user, recipient)) = trello_to_slack_mapping.get_key_value(trello_user)
+        else {
             error!(
                 "No Slack user mapping found for Trello user {trello_user}, skipping notification",
             );
             continue;
         };
+        trello_users_by_slack_user
+            .entry(&recipient.slack_user)
+            .or_default()
+            .push((trello_user, recipient));
+    }
 
-        let markdown_text = compose_pending_reviews_message(pending_reviews)?;
+    for mappings in trello_users_by_slack_user.values_mut() {
+        mappings.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    }
+    trello_users_by_slack_user
+}
 
-        info!(
-            "Sending pending reviews notification to Slack user {slack_user} for Trello user {trello_user}"
-        );
+/// Cards of the given Trello users, without duplicates of cards shared by them
+fn cards_of<T: TrackedCard>(
+    cards_by_user: &mut HashMap<TrelloUser, Vec<T>>,
+    trello_users: &[&TrelloUser],
+) -> Vec<T> {
+    let mut card_ids = HashSet::new();
+    trello_users
+        .iter()
+        .filter_map(|trello_user| cards_by_user.remove(*trello_user))
+        .flatten()
+        .filter(|card| card_ids.insert(card.card_id().to_string()))
+        .collect()
+}
+
+/// IDs of the cards, e.g. to record them in the state
+fn card_ids_of<T: TrackedCard>(cards: &[T]) -> Vec<String> {
+    cards
+        .iter()
+        .map(|card| card.card_id().to_string())
+        .collect()
+}
 
-        slack_poster.post_message(slack_user, &markdown_text)?;
+/// Retires the previous reminders of users without any cards left, so that no outdated reminder stays around.
+/// The reminders are replaced by the text `text_id` or deleted, depending on the update policy.
+fn retire_messages(
+    context: &ActionContext,
+    state: &mut StateStore,
+    action: &str,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    users_with_cards: &HashSet<SlackUser>,
+    text_id: &str,
+) -> Result<()> {
+    if context.slack_poster.update_policy() == UpdatePolicy::Post {
+        return Ok(());
+    }
+
+    let mut retired = HashSet::new();
+
+    for recipient in trello_to_slack_mapping.values() {
+        let slack_user = &recipient.slack_user;
+        if users_with_cards.contains(slack_user) || !retired.insert(slack_user) {
+            continue;
+        }
+        let Some(previous) = state.message(action, &slack_user.0).cloned() else {
+            continue;
+        };
+
+        info!("Retiring the previous reminder of Slack user {slack_user}");
+        let message = Message {
+            text: context
+                .templates
+                .translator(recipient.locale)
+                .text(text_id, &[]),
+            blocks: None,
+        };
+        if let Err(e) = context
+            .slack_poster
+            .retire_message(slack_user, &previous, &message)
+        {
+            error!("Failed to retire the previous reminder of Slack user {slack_user}: {e:#}");
+        }
+
+        state.remember_message(action, &slack_user.0, None)?;
     }
 
     Ok(())
 }
 
-#[derive(Clone, Debug)]
+/// Posts the team digests to their channels, replacing the previous digest of the action in each channel
+fn post_digest(
+    slack_poster: &SlackMessagePoster,
+    state: &mut StateStore,
+    report: &mut DeliveryReport,
+    action: &str,
+    digests: Vec<(&String, Message)>,
+) -> Result<()> {
+    for (channel, message) in digests {
+        info!("Sending digest to channel {channel}");
+        let previous = state.message(action, channel);
+        let result = slack_poster.post_to_channel(channel, &message, previous);
+        if let Some(posted) = report.record_digest(channel, result) {
+            state.remember_message(action, channel, posted)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, Serialize)]
 struct PendingReview {
+    card_id: String,
     card_name: String,
     card_url: String,
+    board_id: String,
+    board_name: String,
+    list_id: String,
+    list_name: String,
     pending_since_days: usize,
+    is_new: bool,
+    is_urgent: bool,
+}
+
+impl TrackedCard for PendingReview {
+    fn card_id(&self) -> &str {
+        &self.card_id
+    }
+
+    fn list_id(&self) -> &str {
+        &self.list_id
+    }
+
+    fn set_new(&mut self, is_new: bool) {
+        self.is_new = is_new;
+    }
+
+    fn is_escalated(&self) -> bool {
+        self.is_urgent
+    }
+}
+
+#[cfg(test)]
+impl PendingReview {
+    /// A review on the team board, named like its ID
+    fn for_test(card_id: &str, list_name: &str, pending_since_days: usize) -> Self {
+        PendingReview {
+            card_id: card_id.to_string(),
+            card_name: card_id.to_string(),
+            card_url: format!("https://trello.com/c/{card_id}"),
+            board_id: "board1".to_string(),
+            board_name: "Team".to_string(),
+            list_id: list_name.to_lowercase(),
+            list_name: list_name.to_string(),
+            pending_since_days,
+            is_new: false,
+            is_urgent: false,
+        }
+    }
 }
 
 fn get_pending_reviews(
     trello_client: &TrelloClient,
+    calendar: &WorkCalendar,
+    labels: &LabelConfig,
+    unassigned: &UnassignedConfig,
     trello_member_id_to_username: &HashMap<String, TrelloUser>,
-    target_lists: impl Iterator<Item = List>,
+    target_lists: impl Iterator<Item = BoardList>,
 ) -> Result<HashMap<TrelloUser, Vec<PendingReview>>> {
     let mut pending_reviews = HashMap::<TrelloUser, Vec<PendingReview>>::new();
 
-    for list in target_lists {
+    for board_list in target_lists {
+        let BoardList {
+            board_id,
+            board_name,
+            list,
+        } = &board_list;
         info!("Processing list '{}' (ID: {})", list.name, list.id);
 
         let cards = trello_client.get_cards(&list.id)?;
 
-        for card in &cards {
-            let trello_users = card
-                .id_members
-                .iter()
-                .filter_map(|user_id| {
-                    let trello_user = trello_member_id_to_username.get(user_id).cloned();
-
-                    if trello_user.is_none() {
-                        error!("Could not find Trello user for member ID {user_id}");
-                    }
-
-                    trello_user
-                })
-                .collect::<Vec<_>>();
-
+        for card in cards.iter().filter(|card| labels.includes(card)) {
+            let trello_users =
+                card_assignees(card, &board_list, unassigned, trello_member_id_to_username);
             if trello_users.is_empty() {
-                info!(
-                    "Skipping card '{}' (ID: {}) with no mapped Trello users",
-                    card.name, card.id
-                );
                 continue;
             }
 
             let last_update = last_update_from_card(card);
 
             let pending_review = PendingReview {
+                card_id: card.id.clone(),
                 card_name: card.name.clone(),
                 card_url: card.url.clone(),
-                pending_since_days: (OffsetDateTime::now_utc() - last_update).whole_days() as usize,
+                board_id: board_id.clone(),
+                board_name: board_name.clone(),
+                list_id: list.id.clone(),
+                list_name: list.name.clone(),
+                pending_since_days: calendar.working_time_since(last_update).whole_days() as usize,
+                is_new: false,
+                is_urgent: labels.is_urgent(card),
             };
             for trello_user in trello_users {
                 pending_reviews
@@ -215,130 +867,267 @@ fn get_pending_reviews(
     Ok(pending_reviews)
 }
 
-fn compose_pending_reviews_message(mut pending_reviews: Vec<PendingReview>) -> Result<String> {
+/// Trello users of the card's members, leaving out members that are not found on the boards
+fn card_members(
+    card: &Card,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+) -> Vec<TrelloUser> {
+    card.id_members
+        .iter()
+        .filter_map(|user_id| {
+            let trello_user = trello_member_id_to_username.get(user_id).cloned();
+
+            if trello_user.is_none() {
+                error!("Could not find Trello user for member ID {user_id}");
+            }
+
+            trello_user
+        })
+        .collect()
+}
+
+/// Mapped Trello users of the card's members, or else the owner of its list
+fn card_assignees(
+    card: &Card,
+    board_list: &BoardList,
+    unassigned: &UnassignedConfig,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+) -> Vec<TrelloUser> {
+    let trello_users = card_members(card, trello_member_id_to_username);
+    if !trello_users.is_empty() {
+        return trello_users;
+    }
+
+    match unassigned.owner(board_list) {
+        Some(owner) => {
+            info!(
+                "Routing card '{}' (ID: {}) with no mapped Trello users to list owner {owner}",
+                card.name, card.id
+            );
+            vec![owner.clone()]
+        }
+        None => {
+            info!(
+                "Skipping card '{}' (ID: {}) with no mapped Trello users",
+                card.name, card.id
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Recipient of a message as provided to the message templates
+#[derive(Serialize)]
+struct UserContext<'a> {
+    trello_user: &'a str,
+    slack_user: &'a str,
+    locale: String,
+}
+
+impl<'a> UserContext<'a> {
+    fn new(recipient: &'a Recipient, trello_user: &'a TrelloUser) -> Self {
+        UserContext {
+            trello_user: &trello_user.0,
+            slack_user: &recipient.slack_user.0,
+            locale: recipient.locale.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PendingReviewsContext<'a> {
+    user: UserContext<'a>,
+    count: usize,
+    reviews: Vec<PendingReview>,
+}
+
+fn compose_pending_reviews_message(
+    templates: &Templates,
+    message_format: MessageFormat,
+    snooze_buttons: bool,
+    recipient: &Recipient,
+    trello_user: &TrelloUser,
+    mut pending_reviews: Vec<PendingReview>,
+) -> Result<Message> {
     pending_reviews.sort_by_key(|review| usize::MAX - review.pending_since_days); // descending
 
-    let mut markdown_text = String::new();
-    writeln!(
-        &mut markdown_text,
-        "**🔎 Du hast {} ausstehende{s1} Review{s2}:**",
-        pending_reviews.len(),
-        s1 = if pending_reviews.len() == 1 { "s" } else { "" },
-        s2 = if pending_reviews.len() > 1 { "s" } else { "" },
-    )?;
-    for PendingReview {
-        card_name,
-        card_url,
-        pending_since_days,
-    } in pending_reviews
-    {
-        write!(&mut markdown_text, "- [{card_name}]({card_url})")?;
-        if pending_since_days >= 1 {
-            write!(
-                &mut markdown_text,
-                " - Wartet seit {pending_since_days} Tag{en} {sirens}",
-                en = if pending_since_days > 1 { "en" } else { "" },
-                sirens = "🚨".repeat(pending_since_days.saturating_sub(1))
-            )?;
-        }
-        writeln!(&mut markdown_text)?;
-    }
-    writeln!(&mut markdown_text, "\n\n")?;
-    writeln!(
-        &mut markdown_text,
-        "Mach das Team glücklich und bearbeite das zeitnah!"
+    let blocks = (message_format == MessageFormat::Blocks).then(|| {
+        pending_reviews_blocks(
+            templates.translator(recipient.locale),
+            &pending_reviews,
+            snooze_buttons,
+        )
+    });
+
+    let text = templates.render(
+        MessageTemplate::PendingReviews,
+        recipient.locale,
+        PendingReviewsContext {
+            user: UserContext::new(recipient, trello_user),
+            count: pending_reviews.len(),
+            reviews: pending_reviews,
+        },
     )?;
 
-    Ok(markdown_text)
+    Ok(Message { text, blocks })
 }
 
-const INACTIVE_WEEKS_THRESHOLD: usize = 2;
-
 /// ACTION: Send notifications for inactive cards
 fn inactive_cards(
-    trello_client: &TrelloClient,
-    slack_poster: &SlackMessagePoster,
-    trello_to_slack_mapping: &HashMap<TrelloUser, SlackUser>,
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
     trello_member_id_to_username: &HashMap<String, TrelloUser>,
-    target_lists: impl Iterator<Item = List>,
+    target_lists: impl Iterator<Item = (BoardList, Threshold)>,
 ) -> Result<()> {
-    let inactive_cards =
-        get_inactive_cards(trello_client, trello_member_id_to_username, target_lists)?;
+    let ActionContext {
+        trello_client,
+        slack_poster,
+        slack_directory: _,
+        calendar,
+        templates,
+        message_format,
+        snooze_buttons,
+        locale,
+        digest,
+        labels,
+        unassigned,
+        boards,
+    } = *context;
 
-    for (trello_user, inactive_cards) in inactive_cards {
-        if inactive_cards.is_empty() {
-            continue;
-        }
-        let Some(slack_user) = trello_to_slack_mapping.get(&trello_user) else {
-            error!(
-                "No Slack user mapping found for Trello user {trello_user}, skipping notification",
-            );
-            continue;
-        };
+    let inactive_cards = get_inactive_cards(
+        trello_client,
+        calendar,
+        labels,
+        unassigned,
+        trello_member_id_to_username,
+        target_lists,
+    )?;
 
-        info!(
-            "Sending inactive cards notification to Slack user {slack_user} for Trello user {trello_user}"
-        );
+    let digests = compose_digests(
+        &inactive_cards,
+        |board_id| digest.inactive_cards_channel_for(boards, board_id),
+        |inactive_cards| {
+            let recipients = trello_to_slack_mapping;
+            compose_inactive_cards_digest(templates, calendar, locale, inactive_cards, recipients)
+        },
+    )?;
 
-        let markdown_text = compose_inactive_cards_message(inactive_cards)?;
-        slack_poster.post_message(slack_user, &markdown_text)?;
-    }
+    let action = ActionConfig::InactiveCards.to_string();
+    let mut report = notify_recipients(
+        context,
+        state,
+        &action,
+        trello_to_slack_mapping,
+        inactive_cards,
+        "no-inactive-cards",
+        |recipient, trello_user, inactive_cards| {
+            compose_inactive_cards_message(
+                templates,
+                message_format,
+                snooze_buttons,
+                calendar,
+                recipient,
+                trello_user,
+                inactive_cards,
+            )
+        },
+    )?;
+    post_digest(slack_poster, state, &mut report, &action, digests)?;
 
-    Ok(())
+    report.finish()
 }
 
 #[derive(Clone, Debug)]
 struct InactiveCard {
+    card_id: String,
     card_name: String,
     card_url: String,
-    pending_since_weeks: usize,
+    board_id: String,
+    board_name: String,
+    list_id: String,
+    list_name: String,
+    pending_since: Duration,
+    threshold: Duration,
+    is_new: bool,
+    is_urgent: bool,
+}
+
+impl InactiveCard {
+    /// One siren for every full working week beyond the threshold, or every full threshold if it is shorter,
+    /// e.g. one siren after three weeks for the default threshold of two weeks
+    fn sirens(&self, calendar: &WorkCalendar) -> usize {
+        if self.pending_since <= self.threshold {
+            return 0;
+        }
+
+        let period = self.threshold.min(calendar.working_week());
+        ((self.pending_since - self.threshold) / period) as usize
+    }
+}
+
+impl TrackedCard for InactiveCard {
+    fn card_id(&self) -> &str {
+        &self.card_id
+    }
+
+    fn list_id(&self) -> &str {
+        &self.list_id
+    }
+
+    fn set_new(&mut self, is_new: bool) {
+        self.is_new = is_new;
+    }
+
+    fn is_escalated(&self) -> bool {
+        self.is_urgent
+    }
 }
 
 fn get_inactive_cards(
     trello_client: &TrelloClient,
+    calendar: &WorkCalendar,
+    labels: &LabelConfig,
+    unassigned: &UnassignedConfig,
     trello_member_id_to_username: &HashMap<String, TrelloUser>,
-    target_lists: impl Iterator<Item = List>,
+    target_lists: impl Iterator<Item = (BoardList, Threshold)>,
 ) -> Result<HashMap<TrelloUser, Vec<InactiveCard>>> {
     let mut inactive_cards = HashMap::<TrelloUser, Vec<InactiveCard>>::new();
 
-    for list in target_lists {
+    for (board_list, threshold) in target_lists {
+        let BoardList {
+            board_id,
+            board_name,
+            list,
+        } = &board_list;
         info!("Processing list '{}' (ID: {})", list.name, list.id);
 
         let cards = trello_client.get_cards(&list.id)?;
 
-        for card in &cards {
-            let trello_users = card
-                .id_members
-                .iter()
-                .filter_map(|user_id| {
-                    let trello_user = trello_member_id_to_username.get(user_id).cloned();
-
-                    if trello_user.is_none() {
-                        error!("Could not find Trello user for member ID {user_id}");
-                    }
-
-                    trello_user
-                })
-                .collect::<Vec<_>>();
-
+        for card in cards.iter().filter(|card| labels.includes(card)) {
+            let trello_users =
+                card_assignees(card, &board_list, unassigned, trello_member_id_to_username);
             if trello_users.is_empty() {
-                info!(
-                    "Skipping card '{}' (ID: {}) with no mapped Trello users",
-                    card.name, card.id
-                );
                 continue;
             }
 
             let in_list_since = moved_to_list_date(card)?;
 
             let inactive_card = InactiveCard {
+                card_id: card.id.clone(),
                 card_name: card.name.clone(),
                 card_url: card.url.clone(),
-                pending_since_weeks: (OffsetDateTime::now_utc() - in_list_since).whole_weeks()
-                    as usize,
+                board_id: board_id.clone(),
+                board_name: board_name.clone(),
+                list_id: list.id.clone(),
+                list_name: list.name.clone(),
+                pending_since: calendar.working_time_since(in_list_since),
+                threshold: calendar.threshold_duration(threshold),
+                is_new: false,
+                is_urgent: labels.is_urgent(card),
             };
 
-            if inactive_card.pending_since_weeks < INACTIVE_WEEKS_THRESHOLD {
+            if inactive_card.pending_since < inactive_card.threshold && !inactive_card.is_urgent {
                 continue; // not inactive enough
             }
 
@@ -354,38 +1143,344 @@ fn get_inactive_cards(
     Ok(inactive_cards)
 }
 
-fn compose_inactive_cards_message(mut inactive_cards: Vec<InactiveCard>) -> Result<String> {
-    inactive_cards.sort_by_key(|card| usize::MAX - card.pending_since_weeks); // descending
+/// Inactive card as provided to the message template
+#[derive(Serialize)]
+struct InactiveCardContext {
+    card_id: String,
+    card_name: String,
+    card_url: String,
+    board_name: String,
+    list_name: String,
+    /// Localized working time since the card was moved to the list
+    age: String,
+    age_hours: i64,
+    threshold_hours: i64,
+    /// One siren for every full working week beyond the threshold, or every full threshold if it is shorter
+    sirens: usize,
+    is_new: bool,
+    is_urgent: bool,
+}
+
+impl InactiveCardContext {
+    fn new(translator: &Translator, calendar: &WorkCalendar, card: InactiveCard) -> Self {
+        InactiveCardContext {
+            age: format_age(translator, calendar, card.pending_since),
+            age_hours: card.pending_since.whole_hours(),
+            threshold_hours: card.threshold.whole_hours(),
+            sirens: card.sirens(calendar),
+            card_id: card.card_id,
+            card_name: card.card_name,
+            card_url: card.card_url,
+            board_name: card.board_name,
+            list_name: card.list_name,
+            is_new: card.is_new,
+            is_urgent: card.is_urgent,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct InactiveCardsContext<'a> {
+    user: UserContext<'a>,
+    count: usize,
+    cards: Vec<InactiveCardContext>,
+}
+
+fn compose_inactive_cards_message(
+    templates: &Templates,
+    message_format: MessageFormat,
+    snooze_buttons: bool,
+    calendar: &WorkCalendar,
+    recipient: &Recipient,
+    trello_user: &TrelloUser,
+    mut inactive_cards: Vec<InactiveCard>,
+) -> Result<Message> {
+    inactive_cards.sort_by_key(|card| std::cmp::Reverse(card.pending_since)); // descending
 
-    let mut markdown_text = String::new();
-    writeln!(
-        &mut markdown_text,
-        "**📝 Folgende {number} Karte{n} {is} seit längerer Zeit im Sprint:**",
-        number = inactive_cards.len(),
-        n = if inactive_cards.len() > 1 { "n" } else { "" },
-        is = if inactive_cards.len() > 1 {
-            "sind"
-        } else {
-            "ist"
+    let translator = templates.translator(recipient.locale);
+    let blocks = (message_format == MessageFormat::Blocks)
+        .then(|| inactive_cards_blocks(translator, calendar, &inactive_cards, snooze_buttons));
+    let cards: Vec<InactiveCardContext> = inactive_cards
+        .into_iter()
+        .map(|card| InactiveCardContext::new(translator, calendar, card))
+        .collect();
+
+    let text = templates.render(
+        MessageTemplate::InactiveCards,
+        recipient.locale,
+        InactiveCardsContext {
+            user: UserContext::new(recipient, trello_user),
+            count: cards.len(),
+            cards,
         },
     )?;
-    for InactiveCard {
-        card_name,
-        card_url,
-        pending_since_weeks,
-    } in inactive_cards
-    {
-        writeln!(
-            &mut markdown_text,
-            "- [{card_name}]({card_url}) - In Liste seit {pending_since_weeks} Wochen {sirens}",
-            sirens = "🚨".repeat(pending_since_weeks.saturating_sub(INACTIVE_WEEKS_THRESHOLD))
-        )?;
+
+    Ok(Message { text, blocks })
+}
+
+/// Formats a working time in the largest fitting unit, e.g. "3 Wochen", "5 Tagen" or "1 Stunde"
+fn format_age(translator: &Translator, calendar: &WorkCalendar, age: Duration) -> String {
+    let weeks = (age / calendar.working_week()) as i64;
+    let (id, value) = if weeks > 0 {
+        ("age-weeks", weeks)
+    } else if age.whole_days() > 0 {
+        ("age-days", age.whole_days())
+    } else {
+        ("age-hours", age.whole_hours())
+    };
+
+    translator.text(id, &[("value", Arg::Number(value))])
+}
+
+/// Renders a message template with sample cards, e.g. to check a customized template
+fn render_template_sample(
+    config: &AppConfig,
+    calendar: &WorkCalendar,
+    templates: &Templates,
+    template: MessageTemplate,
+) -> Result<()> {
+    let (trello_user, recipient) = sample_recipient(config);
+
+    let reviews = sample_reviews();
+    let inactive_cards =
+        sample_inactive_cards(calendar.threshold_duration(config.trello.inactive_threshold));
+
+    let text = match template {
+        MessageTemplate::PendingReviews => {
+            compose_pending_reviews_message(
+                templates,
+                MessageFormat::Markdown,
+                false,
+                &recipient,
+                &trello_user,
+                reviews,
+            )?
+            .text
+        }
+        MessageTemplate::InactiveCards => {
+            compose_inactive_cards_message(
+                templates,
+                MessageFormat::Markdown,
+                false,
+                calendar,
+                &recipient,
+                &trello_user,
+                inactive_cards,
+            )?
+            .text
+        }
+        MessageTemplate::PendingReviewsDigest => {
+            let locale = recipient.locale;
+            let pending_reviews = HashMap::from([(trello_user.clone(), reviews)]);
+            let recipients = HashMap::from([(trello_user, recipient)]);
+            compose_pending_reviews_digest(templates, locale, &pending_reviews, &recipients)?
+                .map(|message| message.text)
+                .unwrap_or_default()
+        }
+        MessageTemplate::InactiveCardsDigest => {
+            let locale = recipient.locale;
+            let inactive_cards = HashMap::from([(trello_user.clone(), inactive_cards)]);
+            let recipients = HashMap::from([(trello_user, recipient)]);
+            compose_inactive_cards_digest(
+                templates,
+                calendar,
+                locale,
+                &inactive_cards,
+                &recipients,
+            )?
+            .map(|message| message.text)
+            .unwrap_or_default()
+        }
+        MessageTemplate::BlockedCards => {
+            compose_blocked_cards_message(
+                templates,
+                MessageFormat::Markdown,
+                false,
+                calendar,
+                &recipient,
+                &trello_user,
+                inactive_cards,
+            )?
+            .text
+        }
+        MessageTemplate::DueDates => {
+            let range = DueDateRange::new(&config.due_dates, config.schedule.timezone);
+            compose_due_dates_message(
+                templates,
+                MessageFormat::Markdown,
+                false,
+                &recipient,
+                &trello_user,
+                sample_due_cards(&range),
+            )?
+            .text
+        }
+        MessageTemplate::WipLimits => {
+            let recipients = HashMap::from([(trello_user.clone(), recipient)]);
+            compose_wip_limits_message(
+                templates,
+                calendar,
+                config.locale,
+                sample_wip_violations(&trello_user),
+                &recipients,
+            )?
+            .text
+        }
+        MessageTemplate::UnassignedCards => {
+            let recipients = HashMap::from([(trello_user.clone(), recipient)]);
+            compose_unassigned_cards_message(
+                templates,
+                calendar,
+                config.locale,
+                sample_unassigned_lists(&trello_user, inactive_cards),
+                &recipients,
+            )?
+            .text
+        }
+    };
+
+    std::io::stdout()
+        .write_all(text.as_bytes())
+        .context("Failed to write rendered template to stdout")
+}
+
+/// The first manually mapped user, or a made up one
+fn sample_recipient(config: &AppConfig) -> (TrelloUser, Recipient) {
+    config.user_mapping.first().map_or_else(
+        || {
+            let recipient = Recipient {
+                slack_user: SlackUser("U0123456789".to_string()),
+                locale: config.locale,
+            };
+            (TrelloUser("trello_user".to_string()), recipient)
+        },
+        |mapping| {
+            let recipient = Recipient {
+                slack_user: mapping.slack_user.clone(),
+                locale: mapping.locale.unwrap_or(config.locale),
+            };
+            (mapping.trello_user.clone(), recipient)
+        },
+    )
+}
+
+fn sample_card(number: usize) -> (String, String, String) {
+    (
+        format!("sample{number}"),
+        format!("Sample card {number}"),
+        format!("https://trello.com/c/sample{number}"),
+    )
+}
+
+fn sample_reviews() -> Vec<PendingReview> {
+    [3, 1, 0]
+        .into_iter()
+        .enumerate()
+        .map(|(index, pending_since_days)| {
+            let (card_id, card_name, card_url) = sample_card(index + 1);
+            PendingReview {
+                card_id,
+                card_name,
+                card_url,
+                board_id: "sample".to_string(),
+                board_name: "Sample board".to_string(),
+                list_id: "review".to_string(),
+                list_name: "Review".to_string(),
+                pending_since_days,
+                is_new: index == 0,
+                is_urgent: false,
+            }
+        })
+        .collect()
+}
+
+fn sample_inactive_cards(threshold: Duration) -> Vec<InactiveCard> {
+    [3, 1]
+        .into_iter()
+        .enumerate()
+        .map(|(index, thresholds)| {
+            let (card_id, card_name, card_url) = sample_card(index + 1);
+            InactiveCard {
+                card_id,
+                card_name,
+                card_url,
+                board_id: "sample".to_string(),
+                board_name: "Sample board".to_string(),
+                list_id: "in-progress".to_string(),
+                list_name: "In Progress".to_string(),
+                pending_since: threshold * thresholds,
+                threshold,
+                is_new: index == 0,
+                is_urgent: false,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Weekday;
+
+    fn monday_to_friday() -> WorkCalendar {
+        let workdays = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ];
+        WorkCalendar::new(&workdays, [], chrono_tz::Tz::UTC).unwrap()
+    }
+
+    fn inactive_card(pending_since: Duration, threshold: Duration) -> InactiveCard {
+        InactiveCard {
+            pending_since,
+            threshold,
+            ..sample_inactive_cards(threshold).remove(0)
+        }
     }
-    writeln!(&mut markdown_text, "\n\n")?;
-    writeln!(
-        &mut markdown_text,
-        "Schau mal nach, ob die Karten zu bearbeiten sind!"
-    )?;
 
-    Ok(markdown_text)
+    #[test]
+    fn it_adds_a_siren_per_week_beyond_the_threshold() {
+        let calendar = monday_to_friday();
+        let week = calendar.working_week();
+        let sirens = |pending_since: Duration, threshold: Duration| {
+            inactive_card(pending_since, threshold).sirens(&calendar)
+        };
+
+        // the default threshold of two weeks keeps one siren per week after the threshold
+        assert_eq!(sirens(week * 2, week * 2), 0);
+        assert_eq!(sirens(week * 3 - Duration::hours(1), week * 2), 0);
+        assert_eq!(sirens(week * 3, week * 2), 1);
+        assert_eq!(sirens(week * 5, week * 2), 3);
+
+        // shorter thresholds add a siren per threshold
+        assert_eq!(sirens(Duration::days(4), Duration::days(2)), 1);
+        assert_eq!(sirens(Duration::hours(36), Duration::hours(12)), 2);
+
+        assert_eq!(sirens(Duration::hours(1), week), 0, "urgent cards");
+    }
+
+    #[test]
+    fn it_formats_ages_in_the_largest_unit() {
+        let calendar = monday_to_friday();
+        let translator = Translator::new(Locale::En);
+        let age = |age: Duration| format_age(&translator, &calendar, age);
+
+        assert_eq!(age(Duration::days(5)), "1 week");
+        assert_eq!(age(Duration::days(12)), "2 weeks");
+        assert_eq!(age(Duration::days(4)), "4 days");
+        assert_eq!(age(Duration::hours(30)), "1 day");
+        assert_eq!(age(Duration::hours(5)), "5 hours");
+        assert_eq!(age(Duration::ZERO), "0 hours");
+
+        let seven_days =
+            WorkCalendar::new(&[Weekday::Monday, Weekday::Sunday], [], chrono_tz::Tz::UTC).unwrap();
+        assert_eq!(
+            format_age(&translator, &seven_days, Duration::days(5)),
+            "2 weeks",
+            "a week is a working week"
+        );
+    }
 }
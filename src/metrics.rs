@@ -0,0 +1,600 @@
+use crate::{
+    BoardList,
+    calendar::WorkCalendar,
+    config::{AppConfig, MetricsConfig, MetricsFormat},
+    fetch_members,
+    list_selector::ListSelector,
+    schema::{Action, ActionType, Card, List},
+    trello::{TrelloClient, creation_date_from_card_id},
+};
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use log::info;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+};
+use time::{Duration, OffsetDateTime, serde::iso8601};
+
+/// Time a card spent in a list
+#[derive(Clone, Debug, Serialize)]
+struct ListStay {
+    list_id: String,
+    list_name: String,
+    #[serde(with = "iso8601")]
+    entered: OffsetDateTime,
+    /// `None` while the card is still in the list
+    #[serde(with = "iso8601::option")]
+    left: Option<OffsetDateTime>,
+}
+
+/// The lists a card has been in during the evaluated period
+#[derive(Debug, Serialize)]
+struct CardHistory {
+    board_id: String,
+    board_name: String,
+    card_id: String,
+    card_name: String,
+    members: Vec<String>,
+    #[serde(with = "iso8601")]
+    created: OffsetDateTime,
+    /// Oldest stay first
+    timeline: Vec<ListStay>,
+}
+
+impl CardHistory {
+    fn board_list(&self, stay: &ListStay) -> BoardList {
+        BoardList {
+            board_id: self.board_id.clone(),
+            board_name: self.board_name.clone(),
+            list: List {
+                id: stay.list_id.clone(),
+                name: stay.list_name.clone(),
+            },
+        }
+    }
+
+    fn is_in(&self, stay: &ListStay, selectors: &[ListSelector]) -> bool {
+        let board_list = self.board_list(stay);
+        selectors
+            .iter()
+            .any(|selector| selector.matches(&board_list))
+    }
+
+    /// Time the card entered the done list it is still in, `None` if it is not done
+    fn completed(&self, done_lists: &[ListSelector]) -> Option<OffsetDateTime> {
+        self.timeline
+            .last()
+            .filter(|stay| stay.left.is_none() && self.is_in(stay, done_lists))
+            .map(|stay| stay.entered)
+    }
+
+    /// First time the card entered a start list before it was completed
+    fn started(
+        &self,
+        start_lists: &[ListSelector],
+        completed: OffsetDateTime,
+    ) -> Option<OffsetDateTime> {
+        self.timeline
+            .iter()
+            .filter(|stay| stay.entered < completed)
+            .find(|stay| self.is_in(stay, start_lists))
+            .map(|stay| stay.entered)
+    }
+}
+
+/// Reconstructs the lists of a card from its creation and list moves, which must be sorted oldest first.
+/// If the history starts with a move, the card is assumed to be in the previous list since its creation.
+fn timeline(created: OffsetDateTime, actions: &[&Action]) -> Vec<ListStay> {
+    let mut stays: Vec<ListStay> = Vec::new();
+
+    for action in actions {
+        let (list_before, list_after) = match action.r#type {
+            ActionType::CreateCard => (None, action.data.list.as_ref()),
+            ActionType::UpdateCard => (
+                action.data.list_before.as_ref(),
+                action.data.list_after.as_ref(),
+            ),
+            ActionType::Other(_) => continue,
+        };
+        let Some(list_after) = list_after else {
+            continue;
+        };
+
+        match (stays.last_mut(), list_before) {
+            (Some(stay), _) => stay.left = Some(action.date),
+            (None, Some(list_before)) => stays.push(ListStay {
+                list_id: list_before.id.clone(),
+                list_name: list_before.name.clone(),
+                entered: created,
+                left: Some(action.date),
+            }),
+            (None, None) => {}
+        }
+
+        stays.push(ListStay {
+            list_id: list_after.id.clone(),
+            list_name: list_after.name.clone(),
+            entered: action.date,
+            left: None,
+        });
+    }
+
+    stays
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Metric {
+    /// Time of a stay in a list
+    Dwell,
+    /// Time from the first entry into a start list until done
+    Cycle,
+    /// Time from the creation of a card until done
+    Lead,
+}
+
+impl Metric {
+    fn name(self) -> &'static str {
+        match self {
+            Metric::Dwell => "dwell_time",
+            Metric::Cycle => "cycle_time",
+            Metric::Lead => "lead_time",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Dimension {
+    List,
+    Board,
+    Member,
+    /// ISO week of the completion
+    Week,
+}
+
+impl Dimension {
+    fn name(self) -> &'static str {
+        match self {
+            Dimension::List => "list",
+            Dimension::Board => "board",
+            Dimension::Member => "member",
+            Dimension::Week => "week",
+        }
+    }
+}
+
+/// Statistics of a metric for one group, e.g. the cycle time of a member
+#[derive(Debug, Serialize)]
+struct Summary {
+    metric: &'static str,
+    dimension: &'static str,
+    group: String,
+    count: usize,
+    mean_hours: f64,
+    p50_hours: f64,
+    p85_hours: f64,
+    p95_hours: f64,
+}
+
+impl Summary {
+    fn new(metric: Metric, dimension: Dimension, group: String, mut hours: Vec<f64>) -> Self {
+        hours.sort_by(f64::total_cmp);
+
+        Summary {
+            metric: metric.name(),
+            dimension: dimension.name(),
+            group,
+            count: hours.len(),
+            mean_hours: round(hours.iter().sum::<f64>() / hours.len() as f64),
+            p50_hours: percentile(&hours, 50),
+            p85_hours: percentile(&hours, 85),
+            p95_hours: percentile(&hours, 95),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CardMetrics {
+    #[serde(flatten)]
+    history: CardHistory,
+    #[serde(with = "iso8601::option")]
+    completed: Option<OffsetDateTime>,
+    cycle_hours: Option<f64>,
+    lead_hours: Option<f64>,
+}
+
+/// Dwell times per list, and cycle and lead times of the done cards per board, member and week
+#[derive(Debug, Serialize)]
+pub struct MetricsReport {
+    #[serde(with = "iso8601")]
+    since: OffsetDateTime,
+    summaries: Vec<Summary>,
+    cards: Vec<CardMetrics>,
+}
+
+impl MetricsReport {
+    /// Times are measured in working time, the weeks in the given time zone
+    fn new(
+        histories: Vec<CardHistory>,
+        config: &MetricsConfig,
+        calendar: &WorkCalendar,
+        timezone: Tz,
+        since: OffsetDateTime,
+    ) -> Self {
+        let hours_between = |start, end| hours(calendar.working_time_between(start, end));
+        let mut samples = BTreeMap::<(Metric, Dimension, String), Vec<f64>>::new();
+        let mut cards = Vec::new();
+
+        for history in histories {
+            for stay in &history.timeline {
+                if let Some(left) = stay.left {
+                    let list = format!("{} › {}", history.board_name, stay.list_name);
+                    samples
+                        .entry((Metric::Dwell, Dimension::List, list))
+                        .or_default()
+                        .push(hours_between(stay.entered, left));
+                }
+            }
+
+            let completed = history.completed(&config.done_lists);
+            let cycle_hours = completed.and_then(|completed| {
+                history
+                    .started(&config.start_lists, completed)
+                    .map(|started| hours_between(started, completed))
+            });
+            let lead_hours = completed.map(|completed| hours_between(history.created, completed));
+
+            if let Some(completed) = completed {
+                let groups = [
+                    (Dimension::Board, history.board_name.clone()),
+                    (Dimension::Week, iso_week(completed, timezone)),
+                ]
+                .into_iter()
+                .chain(
+                    history
+                        .members
+                        .iter()
+                        .map(|member| (Dimension::Member, member.clone())),
+                );
+                for (dimension, group) in groups {
+                    for (metric, value) in
+                        [(Metric::Cycle, cycle_hours), (Metric::Lead, lead_hours)]
+                    {
+                        if let Some(value) = value {
+                            samples
+                                .entry((metric, dimension, group.clone()))
+                                .or_default()
+                                .push(value);
+                        }
+                    }
+                }
+            }
+
+            cards.push(CardMetrics {
+                history,
+                completed,
+                cycle_hours,
+                lead_hours,
+            });
+        }
+
+        MetricsReport {
+            since,
+            summaries: samples
+                .into_iter()
+                .map(|((metric, dimension, group), hours)| {
+                    Summary::new(metric, dimension, group, hours)
+                })
+                .collect(),
+            cards,
+        }
+    }
+
+    /// One line per summary with a header line
+    fn to_csv(&self) -> String {
+        let header = "metric,dimension,group,count,mean_hours,p50_hours,p85_hours,p95_hours";
+        let lines = self.summaries.iter().map(|summary| {
+            format!(
+                "{},{},{},{},{},{},{},{}",
+                summary.metric,
+                summary.dimension,
+                csv_field(&summary.group),
+                summary.count,
+                summary.mean_hours,
+                summary.p50_hours,
+                summary.p85_hours,
+                summary.p95_hours
+            )
+        });
+
+        std::iter::once(header.to_string())
+            .chain(lines)
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+/// Fetches the list moves of the cards of all boards and writes the report to stdout or the output file
+pub fn metrics(
+    config: &AppConfig,
+    metrics: &MetricsConfig,
+    trello_client: &TrelloClient,
+    calendar: &WorkCalendar,
+) -> Result<()> {
+    let since = OffsetDateTime::now_utc() - metrics.period.calendar_duration();
+    let usernames: HashMap<String, String> = fetch_members(config, trello_client)?
+        .into_iter()
+        .map(|member| (member.id, member.username))
+        .collect();
+
+    let mut histories = Vec::new();
+    for board_id in &config.trello.board_ids {
+        histories.extend(get_card_histories(
+            trello_client,
+            board_id,
+            since,
+            &usernames,
+        )?);
+    }
+    info!(
+        "Reconstructed the list timelines of {} cards",
+        histories.len()
+    );
+
+    let report = MetricsReport::new(
+        histories,
+        metrics,
+        calendar,
+        config.schedule.timezone,
+        since,
+    );
+    let output = match metrics.format {
+        MetricsFormat::Csv => report.to_csv(),
+        MetricsFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+    };
+
+    match &metrics.output {
+        Some(path) => std::fs::write(path, output)
+            .with_context(|| format!("Could not write the metrics to {}", path.display())),
+        None => std::io::stdout()
+            .write_all(output.as_bytes())
+            .context("Could not write the metrics"),
+    }
+}
+
+/// List timelines of the cards of a board with list moves since the given date
+fn get_card_histories(
+    trello_client: &TrelloClient,
+    board_id: &str,
+    since: OffsetDateTime,
+    usernames: &HashMap<String, String>,
+) -> Result<Vec<CardHistory>> {
+    let actions = trello_client.get_board_actions(board_id, since)?;
+    let cards: HashMap<String, Card> = trello_client
+        .get_board_cards(board_id)?
+        .into_iter()
+        .map(|card| (card.id.clone(), card))
+        .collect();
+    info!(
+        "Fetched {} list moves of {} cards on board {board_id}",
+        actions.len(),
+        cards.len()
+    );
+
+    let mut actions_by_card = BTreeMap::<&str, Vec<&Action>>::new();
+    for action in &actions {
+        actions_by_card
+            .entry(&action.data.card.id)
+            .or_default()
+            .push(action);
+    }
+
+    actions_by_card
+        .into_iter()
+        .map(|(card_id, mut card_actions)| {
+            card_actions.sort_by_key(|action| action.date);
+            let card = cards.get(card_id);
+            let latest = &card_actions[card_actions.len() - 1].data;
+            let created = creation_date_from_card_id(card_id)?;
+
+            Ok(CardHistory {
+                board_id: board_id.to_string(),
+                board_name: latest.board.name.clone(),
+                card_id: card_id.to_string(),
+                // deleted cards are only known from their actions
+                card_name: card.map_or_else(|| latest.card.name.clone(), |card| card.name.clone()),
+                members: card
+                    .iter()
+                    .flat_map(|card| &card.id_members)
+                    .filter_map(|member_id| usernames.get(member_id).cloned())
+                    .collect(),
+                created,
+                timeline: timeline(created, &card_actions),
+            })
+        })
+        .collect()
+}
+
+fn hours(duration: Duration) -> f64 {
+    round(duration.as_seconds_f64() / 3600.0)
+}
+
+/// Rounds to one decimal place
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Nearest-rank percentile of values sorted ascending
+fn percentile(sorted: &[f64], percent: usize) -> f64 {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// ISO week of a date in the time zone, e.g. `2025-W07`
+fn iso_week(date: OffsetDateTime, timezone: Tz) -> String {
+    use chrono::Datelike;
+
+    let week = chrono::DateTime::from_timestamp(date.unix_timestamp(), 0)
+        .unwrap_or_default()
+        .with_timezone(&timezone)
+        .iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Quotes a CSV field if necessary
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Weekday, format_description::well_known::Rfc3339, macros::datetime};
+
+    const START: OffsetDateTime = datetime!(2025-01-06 00:00 UTC);
+
+    /// A creation in a list (without `list_before`) or a move between lists
+    fn list_action(day: i64, list_before: Option<&str>, list_after: &str) -> Action {
+        let list = |name: &str| serde_json::json!({ "id": name.to_lowercase(), "name": name });
+        let (r#type, list_after, created_in) = match list_before {
+            Some(_) => ("updateCard", Some(list(list_after)), None),
+            None => ("createCard", None, Some(list(list_after))),
+        };
+
+        serde_json::from_value(serde_json::json!({
+            "id": format!("action{day}"),
+            "idMemberCreator": "m1",
+            "date": (START + Duration::days(day)).format(&Rfc3339).unwrap(),
+            "type": r#type,
+            "appCreator": null,
+            "data": {
+                "board": { "id": "board1", "name": "Team", "shortLink": "b1" },
+                "card": { "id": "card1", "idShort": 1, "name": "Card", "shortLink": "c1" },
+                "list": created_in,
+                "listBefore": list_before.map(list),
+                "listAfter": list_after,
+            },
+            "memberCreator": {
+                "id": "m1",
+                "username": "alice",
+                "fullName": "Alice",
+                "initials": "A",
+                "activityBlocked": false,
+                "nonPublicAvailable": false,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn history(actions: &[Action]) -> CardHistory {
+        let actions: Vec<&Action> = actions.iter().collect();
+        CardHistory {
+            board_id: "board1".to_string(),
+            board_name: "Team".to_string(),
+            card_id: "card1".to_string(),
+            card_name: "Card".to_string(),
+            members: vec!["alice".to_string()],
+            created: START,
+            timeline: timeline(START, &actions),
+        }
+    }
+
+    fn report(histories: Vec<CardHistory>) -> MetricsReport {
+        let config = MetricsConfig {
+            period: "4w".parse().unwrap(),
+            start_lists: vec!["In Progress".parse().unwrap()],
+            done_lists: vec!["Done".parse().unwrap()],
+            format: MetricsFormat::Csv,
+            output: None,
+        };
+        let every_day = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+            Weekday::Saturday,
+            Weekday::Sunday,
+        ];
+        let calendar = WorkCalendar::new(&every_day, [], chrono_tz::Tz::UTC).unwrap();
+
+        MetricsReport::new(histories, &config, &calendar, chrono_tz::UTC, START)
+    }
+
+    #[test]
+    fn it_reconstructs_the_list_timeline() {
+        // the card was created before the evaluated period
+        let card = history(&[
+            list_action(1, Some("Backlog"), "In Progress"),
+            list_action(3, Some("In Progress"), "Review"),
+        ]);
+
+        let stays: Vec<(&str, OffsetDateTime, Option<OffsetDateTime>)> = card
+            .timeline
+            .iter()
+            .map(|stay| (stay.list_name.as_str(), stay.entered, stay.left))
+            .collect();
+        assert_eq!(
+            stays,
+            [
+                ("Backlog", START, Some(START + Duration::days(1))),
+                (
+                    "In Progress",
+                    START + Duration::days(1),
+                    Some(START + Duration::days(3))
+                ),
+                ("Review", START + Duration::days(3), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_dwell_cycle_and_lead_times() {
+        let done = history(&[
+            list_action(0, None, "Backlog"),
+            list_action(1, Some("Backlog"), "In Progress"),
+            list_action(3, Some("In Progress"), "Review"),
+            list_action(4, Some("Review"), "In Progress"),
+            list_action(6, Some("In Progress"), "Done"),
+        ]);
+        let in_review = history(&[
+            list_action(0, None, "In Progress"),
+            list_action(2, Some("In Progress"), "Review"),
+        ]);
+
+        let csv = report(vec![done, in_review]).to_csv();
+
+        let expected_lines = [
+            "metric,dimension,group,count,mean_hours,p50_hours,p85_hours,p95_hours",
+            "dwell_time,list,Team › In Progress,3,48,48,48,48",
+            "dwell_time,list,Team › Review,1,24,24,24,24",
+            "cycle_time,board,Team,1,120,120,120,120",
+            "cycle_time,member,alice,1,120,120,120,120",
+            "lead_time,week,2025-W02,1,144,144,144,144",
+        ];
+        for line in expected_lines {
+            assert!(
+                csv.contains(&format!("{line}\n")),
+                "{line} missing in:\n{csv}"
+            );
+        }
+        assert_eq!(csv.lines().count(), 10, "{csv}");
+    }
+
+    #[test]
+    fn it_computes_nearest_rank_percentiles() {
+        let values: Vec<f64> = (1..=20).map(f64::from).collect();
+
+        assert_eq!(
+            [50, 85, 95].map(|percent| percentile(&values, percent).to_string()),
+            ["10", "17", "19"]
+        );
+        assert_eq!(percentile(&[3.5], 95).to_string(), "3.5");
+        assert_eq!(csv_field("Team, Ops › Review"), "\"Team, Ops › Review\"");
+    }
+}
@@ -0,0 +1,165 @@
+use crate::config::RetryConfig;
+use anyhow::{Context, Result};
+use log::warn;
+use reqwest::{
+    StatusCode,
+    blocking::{RequestBuilder, Response},
+    header::{HeaderMap, RETRY_AFTER},
+};
+use std::time::Duration;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Retries requests that failed due to rate limits, server errors or transport errors with exponential backoff
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    /// Whether a request may be repeated even though the server might have processed it already
+    idempotent: bool,
+}
+
+impl RetryPolicy {
+    pub fn new(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            idempotent: true,
+        }
+    }
+
+    /// Policy for requests with side effects, e.g. posting a message, which must not be repeated
+    /// after a server error or timeout as they might have been processed. Only rate limits and
+    /// failed connections are retried, as the request has not been processed in these cases.
+    #[must_use]
+    pub fn non_idempotent(self) -> Self {
+        RetryPolicy {
+            idempotent: false,
+            ..self
+        }
+    }
+
+    /// Sends the request and returns the first response that is not retryable, or the last one.
+    /// Non-success responses are returned as well, so that callers can report their body.
+    pub fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let mut attempt = 1;
+
+        loop {
+            // requests with streaming bodies cannot be cloned and thus not be retried
+            let Some(retry_request) = request.try_clone().filter(|_| attempt < self.max_attempts)
+            else {
+                return request.send().context("Failed to send request");
+            };
+
+            let delay = match retry_request.send() {
+                Ok(response) if !is_retryable(response.status(), self.idempotent) => {
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    let delay = retry_after(response.headers()).unwrap_or(self.backoff(attempt));
+                    warn!(
+                        "Request to {} failed with status {} (attempt {attempt}/{}), retrying in {delay:?}",
+                        response.url(),
+                        response.status(),
+                        self.max_attempts
+                    );
+                    delay
+                }
+                Err(e)
+                    if e.is_connect()
+                        || (self.idempotent && (e.is_timeout() || e.is_request())) =>
+                {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        "Request failed (attempt {attempt}/{}), retrying in {delay:?}: {e}",
+                        self.max_attempts
+                    );
+                    delay
+                }
+                Err(e) => return Err(e).context("Failed to send request"),
+            };
+
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff, doubling the delay after each attempt
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(MAX_BACKOFF)
+    }
+}
+
+/// Rate limits (429) and server errors (5xx) are temporary, all other errors are fatal.
+/// Server errors are only retried for idempotent requests, as they might have been processed.
+fn is_retryable(status: StatusCode, idempotent: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (idempotent && status.is_server_error())
+}
+
+/// Reads the `Retry-After` header, which both Trello and Slack send in seconds
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds: u64 = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_BACKOFF))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let policy = RetryPolicy::new(&RetryConfig {
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+        });
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(20), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn it_distinguishes_retryable_errors() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS, true));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY, true));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED, true));
+        assert!(!is_retryable(StatusCode::NOT_FOUND, true));
+        assert!(!is_retryable(StatusCode::OK, true));
+    }
+
+    #[test]
+    fn it_only_retries_rate_limits_of_non_idempotent_requests() {
+        let policy = RetryPolicy::new(&RetryConfig {
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+        })
+        .non_idempotent();
+
+        assert!(!policy.idempotent);
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS, false));
+        assert!(!is_retryable(StatusCode::INTERNAL_SERVER_ERROR, false));
+        assert!(!is_retryable(StatusCode::GATEWAY_TIMEOUT, false));
+    }
+
+    #[test]
+    fn it_reads_the_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+        assert_eq!(retry_after(&headers), Some(MAX_BACKOFF));
+    }
+}
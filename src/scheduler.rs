@@ -0,0 +1,92 @@
+use crate::config::{ActionConfig, ScheduleConfig, ScheduledJob};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use log::{error, info};
+
+/// Runs the configured jobs forever, each time their cron expression matches in the configured time zone
+pub fn run_schedule(
+    config: &ScheduleConfig,
+    mut run: impl FnMut(&ActionConfig) -> Result<()>,
+) -> Result<()> {
+    if config.jobs.is_empty() {
+        bail!("No schedules configured, cannot proceed with serve command");
+    }
+
+    let now = Utc::now().with_timezone(&config.timezone);
+    let mut next_runs = config
+        .jobs
+        .iter()
+        .map(|job| next_run(job, &now))
+        .collect::<Result<Vec<_>>>()?;
+
+    loop {
+        let Some((index, next_run_time)) = next_runs
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, next_run)| **next_run)
+            .map(|(index, next_run)| (index, *next_run))
+        else {
+            bail!("No upcoming runs in schedule");
+        };
+        let job = &config.jobs[index];
+
+        info!("Next run: {} at {next_run_time}", job.action);
+
+        let wait = (next_run_time.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        std::thread::sleep(wait);
+
+        info!("Running scheduled action: {}", job.action);
+
+        if let Err(e) = run(&job.action) {
+            error!("Scheduled action {} failed: {e:#}", job.action);
+        }
+
+        next_runs[index] = next_run(job, &next_run_time)?;
+    }
+}
+
+/// Next time the job is due strictly after `after`
+fn next_run(job: &ScheduledJob, after: &DateTime<Tz>) -> Result<DateTime<Tz>> {
+    Ok(job.cron.find_next_occurrence(after, false)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_follows_daylight_saving_time() {
+        let job = ScheduledJob {
+            action: ActionConfig::PendingReviews,
+            cron: croner::Cron::new("0 9 * * 1-5").parse().unwrap(),
+        };
+        let timezone: Tz = "Europe/Berlin".parse().unwrap();
+
+        // Friday before the switch to summer time on Sunday, 2025-03-30
+        let friday = timezone.with_ymd_and_hms(2025, 3, 28, 10, 0, 0).unwrap();
+
+        let monday = next_run(&job, &friday).unwrap();
+
+        assert_eq!(
+            monday.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 3, 31, 7, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run(&job, &monday).unwrap().with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 4, 1, 7, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run(
+                &job,
+                &timezone.with_ymd_and_hms(2025, 3, 27, 10, 0, 0).unwrap()
+            )
+            .unwrap()
+            .with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 3, 28, 8, 0, 0).unwrap()
+        );
+    }
+}
@@ -10,8 +10,15 @@ pub struct Card {
     pub name: String,
     #[serde(with = "iso8601")]
     pub date_last_activity: OffsetDateTime,
+    #[serde(default)]
     pub actions: Vec<Action>,
     pub url: String,
+    #[serde(default, with = "iso8601::option")]
+    pub due: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub due_complete: bool,
+    #[serde(default)]
+    pub labels: Vec<Label>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -99,6 +106,16 @@ pub struct List {
     pub name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Label {
+    pub id: String,
+    /// Empty for labels that only have a color
+    #[serde(default)]
+    pub name: String,
+    pub color: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Old {
@@ -120,12 +137,14 @@ pub struct MemberCreator {
     pub non_public_available: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct Member {
     pub id: String,
     pub username: String,
     pub full_name: String,
+    /// Only visible to members of the same Trello workspace or enterprise
+    pub email: Option<String>,
 }
 
 #[cfg(test)]
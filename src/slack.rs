@@ -1,34 +1,862 @@
-use crate::{SlackUser, config::SlackConfig};
-use anyhow::{Result, bail};
+use crate::{
+    SlackUser, TrelloUser,
+    config::{SlackConfig, UpdatePolicy},
+    retry::RetryPolicy,
+};
+use anyhow::{Context, Result, bail};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Composed notification. If there are Block Kit blocks, the text is the fallback for clients that cannot render them.
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub text: String,
+    pub blocks: Option<serde_json::Value>,
+}
+
+/// A message posted to Slack, identified by its channel and timestamp
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostedMessage {
+    pub channel: String,
+    pub ts: String,
+}
+
+/// Destination of composed messages
+pub trait MessageSink {
+    /// Sends the message to a Slack user or channel ID, `recipient` describes it for logs and dry runs.
+    /// Returns the posted message, unless it was not actually posted.
+    fn send(
+        &self,
+        channel: &str,
+        recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>>;
+
+    /// Replaces the content of a previously posted message
+    fn update(
+        &self,
+        previous: &PostedMessage,
+        recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>>;
+
+    fn delete(&self, previous: &PostedMessage, recipient: &str) -> Result<()>;
+}
 
 pub struct SlackMessagePoster {
-    client: reqwest::blocking::Client,
-    bot_token: String,
+    sink: Box<dyn MessageSink>,
+    update_policy: UpdatePolicy,
 }
 
 impl SlackMessagePoster {
-    pub fn new(client: reqwest::blocking::Client, config: &SlackConfig) -> Self {
+    pub fn new(
+        client: reqwest::blocking::Client,
+        retry: RetryPolicy,
+        config: &SlackConfig,
+    ) -> Result<Self> {
+        let sink: Box<dyn MessageSink> = if config.dry_run {
+            Box::new(DryRunSink::new(config.dry_run_output.clone())?)
+        } else {
+            Box::new(SlackApiSink {
+                client,
+                // posting, updating or deleting a message twice would duplicate the message or fail
+                retry: retry.non_idempotent(),
+                api_url: config.api_url.trim_end_matches('/').to_string(),
+                bot_token: config.bot_token().cloned().context(
+                    "A Slack bot token is required to post messages, unless in a dry run",
+                )?,
+            })
+        };
+
+        Ok(Self::with_sink(sink, config.update_policy))
+    }
+
+    pub fn with_sink(sink: Box<dyn MessageSink>, update_policy: UpdatePolicy) -> Self {
         SlackMessagePoster {
+            sink,
+            update_policy,
+        }
+    }
+
+    pub fn update_policy(&self) -> UpdatePolicy {
+        self.update_policy
+    }
+
+    /// Sends a reminder to the user, replacing the `previous` reminder according to the [`UpdatePolicy`]
+    pub fn post_message(
+        &self,
+        slack_user: &SlackUser,
+        trello_user: &TrelloUser,
+        message: &Message,
+        previous: Option<&PostedMessage>,
+    ) -> Result<Option<PostedMessage>> {
+        self.replace(
+            &slack_user.0,
+            &format!("Slack user {slack_user} (Trello user {trello_user})"),
+            message,
+            previous,
+        )
+    }
+
+    /// Posts a message to a channel, e.g. a team digest, replacing the `previous` message according to the [`UpdatePolicy`]
+    pub fn post_to_channel(
+        &self,
+        channel: &str,
+        message: &Message,
+        previous: Option<&PostedMessage>,
+    ) -> Result<Option<PostedMessage>> {
+        self.replace(channel, &format!("channel {channel}"), message, previous)
+    }
+
+    /// Takes care of a previous reminder that has become obsolete, since there is nothing left to remind of.
+    /// It is updated to the `message` or deleted according to the [`UpdatePolicy`].
+    pub fn retire_message(
+        &self,
+        slack_user: &SlackUser,
+        previous: &PostedMessage,
+        message: &Message,
+    ) -> Result<()> {
+        let recipient = format!("Slack user {slack_user}");
+        match self.update_policy {
+            UpdatePolicy::Post => Ok(()),
+            UpdatePolicy::Update => self.sink.update(previous, &recipient, message).map(|_| ()),
+            UpdatePolicy::Repost => self.sink.delete(previous, &recipient),
+        }
+    }
+
+    fn replace(
+        &self,
+        channel: &str,
+        recipient: &str,
+        message: &Message,
+        previous: Option<&PostedMessage>,
+    ) -> Result<Option<PostedMessage>> {
+        let Some(previous) = previous else {
+            return self.sink.send(channel, recipient, message);
+        };
+
+        match self.update_policy {
+            UpdatePolicy::Post => self.sink.send(channel, recipient, message),
+            UpdatePolicy::Update => match self.sink.update(previous, recipient, message) {
+                Err(e) if is_gone(&e) => {
+                    warn!(
+                        "Could not update the previous message to {recipient}, posting a new one: {e:#}"
+                    );
+                    self.sink.send(channel, recipient, message)
+                }
+                result => result,
+            },
+            UpdatePolicy::Repost => {
+                if let Err(e) = self.sink.delete(previous, recipient) {
+                    warn!("Could not delete the previous message to {recipient}: {e:#}");
+                }
+                self.sink.send(channel, recipient, message)
+            }
+        }
+    }
+}
+
+/// Whether the previous message cannot be updated anymore, e.g. because the user deleted it
+fn is_gone(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<SlackError>(),
+        Some(
+            SlackError::MessageNotFound
+                | SlackError::CantUpdateMessage
+                | SlackError::EditWindowClosed
+        )
+    )
+}
+
+/// Posts messages via the Slack API
+pub struct SlackApiSink {
+    client: reqwest::blocking::Client,
+    retry: RetryPolicy,
+    api_url: String,
+    bot_token: String,
+}
+
+impl SlackApiSink {
+    fn call(&self, method: &str, payload: &serde_json::Value) -> Result<SlackResponse> {
+        let response = self.retry.send(
+            self.client
+                .post(format!("{}/{method}", self.api_url))
+                .bearer_auth(&self.bot_token)
+                .json(payload),
+        )?;
+
+        if !response.status().is_success() {
+            bail!("Failed to call {method}: {:?}", response.text()?);
+        }
+
+        response.json().context("Could not parse Slack response")
+    }
+
+    /// Calls a method that posts or updates a message
+    fn call_with_message(
+        &self,
+        method: &str,
+        mut payload: serde_json::Value,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>> {
+        // `markdown_text` cannot be combined with blocks, so the fallback is sent as `text`
+        match &message.blocks {
+            Some(blocks) => {
+                payload["text"] = message.text.clone().into();
+                payload["blocks"] = blocks.clone();
+            }
+            None => payload["markdown_text"] = message.text.clone().into(),
+        }
+
+        let mut response = self.call(method, &payload)?;
+        let posted = response
+            .channel
+            .take()
+            .zip(response.ts.take())
+            .map(|(channel, ts)| PostedMessage { channel, ts });
+        response.into_result()?;
+
+        Ok(posted)
+    }
+}
+
+impl MessageSink for SlackApiSink {
+    fn send(
+        &self,
+        channel: &str,
+        _recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>> {
+        self.call_with_message(
+            "chat.postMessage",
+            serde_json::json!({ "channel": channel }),
+            message,
+        )
+    }
+
+    fn update(
+        &self,
+        previous: &PostedMessage,
+        _recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>> {
+        // Slack keeps the blocks of the previous message unless they are replaced,
+        // so a message without blocks is sent as a single markdown block
+        let replacement;
+        let message = if message.blocks.is_some() {
+            message
+        } else {
+            replacement = Message {
+                text: message.text.clone(),
+                blocks: Some(serde_json::json!([{ "type": "markdown", "text": message.text }])),
+            };
+            &replacement
+        };
+
+        self.call_with_message(
+            "chat.update",
+            serde_json::json!({ "channel": previous.channel, "ts": previous.ts }),
+            message,
+        )
+    }
+
+    fn delete(&self, previous: &PostedMessage, _recipient: &str) -> Result<()> {
+        self.call(
+            "chat.delete",
+            &serde_json::json!({ "channel": previous.channel, "ts": previous.ts }),
+        )?
+        .into_result()
+    }
+}
+
+/// Looks up the members of the Slack workspace, e.g. to map Trello users automatically.
+/// Requires the `users:read` scope, and `users:read.email` to match by email address.
+pub struct SlackDirectory {
+    client: reqwest::blocking::Client,
+    retry: RetryPolicy,
+    api_url: String,
+    /// Only required once the directory is used, so that dry runs work without a token
+    bot_token: Option<String>,
+}
+
+/// Member of the Slack workspace as returned by `users.list`
+#[derive(Clone, Debug, Deserialize)]
+pub struct SlackMember {
+    pub id: String,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub is_bot: bool,
+    pub real_name: Option<String>,
+    #[serde(default)]
+    pub profile: SlackProfile,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SlackProfile {
+    pub email: Option<String>,
+    pub real_name: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsersListResponse {
+    #[serde(flatten)]
+    response: SlackResponse,
+    #[serde(default)]
+    members: Vec<SlackMember>,
+}
+
+impl SlackDirectory {
+    pub fn new(
+        client: reqwest::blocking::Client,
+        retry: RetryPolicy,
+        config: &SlackConfig,
+    ) -> Self {
+        SlackDirectory {
             client,
-            bot_token: config.bot_token.clone(),
+            retry,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            bot_token: config.bot_token().cloned(),
         }
     }
 
-    pub fn post_message(&self, slack_user: &SlackUser, message: &str) -> Result<()> {
-        let response = self
-            .client
-            .post("https://slack.com/api/chat.postMessage")
-            .bearer_auth(&self.bot_token)
-            .json(&serde_json::json!({
-                "channel": slack_user.0,
-                "markdown_text": message
-            }))
-            .send()?;
+    /// Calls a read method, which takes its arguments as a form
+    fn call<T: DeserializeOwned>(&self, method: &str, form: &[(&str, &str)]) -> Result<T> {
+        let bot_token = self
+            .bot_token
+            .as_ref()
+            .context("A Slack bot token is required to look up Slack users and channels")?;
+        let response = self.retry.send(
+            self.client
+                .post(format!("{}/{method}", self.api_url))
+                .bearer_auth(bot_token)
+                .form(form),
+        )?;
 
         if !response.status().is_success() {
-            bail!("Failed to send message: {:?}", response.text()?);
+            bail!("Failed to call {method}: {:?}", response.text()?);
+        }
+
+        response.json().context("Could not parse Slack response")
+    }
+
+    /// All members of the workspace, following the pagination of `users.list`
+    pub fn members(&self) -> Result<Vec<SlackMember>> {
+        let mut members = Vec::new();
+        let mut cursor = String::new();
+
+        loop {
+            let mut page: UsersListResponse =
+                self.call("users.list", &[("limit", "200"), ("cursor", &cursor)])?;
+            members.append(&mut page.members);
+            cursor = page
+                .response
+                .response_metadata
+                .as_mut()
+                .map(|metadata| std::mem::take(&mut metadata.next_cursor))
+                .unwrap_or_default();
+            page.response
+                .into_result()
+                .context("Failed to list the Slack users")?;
+
+            if cursor.is_empty() {
+                break;
+            }
+        }
+
+        info!("Fetched {} Slack users", members.len());
+
+        Ok(members)
+    }
+
+    /// Fails if the Slack user does not exist, e.g. with [`SlackError::UserNotFound`]
+    pub fn check_user(&self, slack_user: &SlackUser) -> Result<()> {
+        self.call::<SlackResponse>("users.info", &[("user", &slack_user.0)])?
+            .into_result()
+    }
+
+    /// Fails if the channel does not exist or is not visible to the bot
+    pub fn check_channel(&self, channel: &str) -> Result<()> {
+        self.call::<SlackResponse>("conversations.info", &[("channel", channel)])?
+            .into_result()
+    }
+}
+
+/// Common envelope of Slack Web API responses, which use HTTP 200 also for most errors
+#[derive(Debug, Deserialize)]
+pub struct SlackResponse {
+    pub ok: bool,
+    /// Channel and timestamp of a posted message
+    pub channel: Option<String>,
+    pub ts: Option<String>,
+    pub error: Option<SlackError>,
+    pub warning: Option<String>,
+    pub response_metadata: Option<ResponseMetadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ResponseMetadata {
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub messages: Vec<String>,
+    /// Cursor of the next page of a paginated method, empty on the last page
+    #[serde(default)]
+    pub next_cursor: String,
+}
+
+impl SlackResponse {
+    /// Logs the warnings of the response and turns `ok: false` into an error
+    pub fn into_result(self) -> Result<()> {
+        if let Some(warning) = &self.warning {
+            warn!("Slack API warning: {warning}");
+        }
+        if let Some(metadata) = &self.response_metadata {
+            for message in metadata.warnings.iter().chain(&metadata.messages) {
+                warn!("Slack API warning: {message}");
+            }
+        }
+
+        if self.ok {
+            return Ok(());
+        }
+
+        Err(self
+            .error
+            .unwrap_or_else(|| SlackError::Other("unknown_error".to_string()))
+            .into())
+    }
+}
+
+/// Error codes of the Slack Web API, cf. <https://api.slack.com/methods/chat.postMessage#errors>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlackError {
+    NotAuthed,
+    InvalidAuth,
+    AccountInactive,
+    TokenRevoked,
+    MissingScope,
+    ChannelNotFound,
+    NotInChannel,
+    IsArchived,
+    RateLimited,
+    InvalidBlocks,
+    MsgTooLong,
+    NoText,
+    MessageNotFound,
+    CantUpdateMessage,
+    EditWindowClosed,
+    UserNotFound,
+    Other(String),
+}
+
+impl<'de> Deserialize<'de> for SlackError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "not_authed" => SlackError::NotAuthed,
+            "invalid_auth" => SlackError::InvalidAuth,
+            "account_inactive" => SlackError::AccountInactive,
+            "token_revoked" => SlackError::TokenRevoked,
+            "missing_scope" => SlackError::MissingScope,
+            "channel_not_found" => SlackError::ChannelNotFound,
+            "not_in_channel" => SlackError::NotInChannel,
+            "is_archived" => SlackError::IsArchived,
+            "ratelimited" | "rate_limited" => SlackError::RateLimited,
+            "invalid_blocks" => SlackError::InvalidBlocks,
+            "msg_too_long" => SlackError::MsgTooLong,
+            "no_text" => SlackError::NoText,
+            "message_not_found" => SlackError::MessageNotFound,
+            "cant_update_message" => SlackError::CantUpdateMessage,
+            "edit_window_closed" => SlackError::EditWindowClosed,
+            "user_not_found" => SlackError::UserNotFound,
+            other => SlackError::Other(other.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for SlackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlackError::NotAuthed => write!(f, "not_authed: no authentication token provided"),
+            SlackError::InvalidAuth => write!(f, "invalid_auth: invalid authentication token"),
+            SlackError::AccountInactive => {
+                write!(f, "account_inactive: the bot account is inactive")
+            }
+            SlackError::TokenRevoked => write!(f, "token_revoked: the token has been revoked"),
+            SlackError::MissingScope => {
+                write!(f, "missing_scope: the token lacks a required scope")
+            }
+            SlackError::ChannelNotFound => {
+                write!(
+                    f,
+                    "channel_not_found: check the Slack user ID of the mapping"
+                )
+            }
+            SlackError::NotInChannel => write!(f, "not_in_channel: the bot is not in the channel"),
+            SlackError::IsArchived => write!(f, "is_archived: the channel is archived"),
+            SlackError::RateLimited => write!(f, "ratelimited: too many requests"),
+            SlackError::InvalidBlocks => {
+                write!(f, "invalid_blocks: the message blocks are invalid")
+            }
+            SlackError::MsgTooLong => write!(f, "msg_too_long: the message is too long"),
+            SlackError::NoText => write!(f, "no_text: the message is empty"),
+            SlackError::MessageNotFound => {
+                write!(f, "message_not_found: the message was deleted")
+            }
+            SlackError::CantUpdateMessage => {
+                write!(f, "cant_update_message: the message cannot be updated")
+            }
+            SlackError::EditWindowClosed => {
+                write!(
+                    f,
+                    "edit_window_closed: the message is too old to be updated"
+                )
+            }
+            SlackError::UserNotFound => write!(f, "user_not_found: the Slack user does not exist"),
+            SlackError::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SlackError {}
+
+/// Collects the outcome of all notifications of a run, so that a single failing recipient does not abort the run
+#[derive(Default)]
+pub struct DeliveryReport {
+    sent: usize,
+    /// Description of the recipient and the error
+    failures: Vec<(String, anyhow::Error)>,
+}
+
+impl DeliveryReport {
+    /// Returns the result of the notification if it was delivered
+    pub fn record<T>(
+        &mut self,
+        slack_user: &SlackUser,
+        trello_user: &TrelloUser,
+        result: Result<T>,
+    ) -> Option<T> {
+        self.record_delivery(
+            format!("Slack user {slack_user} (Trello user {trello_user})"),
+            result,
+        )
+    }
+
+    /// Returns the result of the digest if it was delivered to the channel
+    pub fn record_digest<T>(&mut self, channel: &str, result: Result<T>) -> Option<T> {
+        self.record_delivery(format!("Channel {channel}"), result)
+    }
+
+    fn record_delivery<T>(&mut self, recipient: String, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.sent += 1;
+                Some(value)
+            }
+            Err(e) => {
+                error!("Failed to notify {recipient}: {e:#}");
+                self.failures.push((recipient, e));
+                None
+            }
         }
+    }
+
+    /// Logs a summary and fails if any notification could not be delivered
+    pub fn finish(self) -> Result<()> {
+        info!(
+            "Delivered {} notification(s), {} failed",
+            self.sent,
+            self.failures.len()
+        );
+
+        if self.failures.is_empty() {
+            return Ok(());
+        }
+
+        let failures = self
+            .failures
+            .iter()
+            .map(|(recipient, e)| format!("- {recipient}: {e:#}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        bail!(
+            "Failed to deliver {} notification(s):\n{failures}",
+            self.failures.len()
+        )
+    }
+}
+
+/// Renders messages to stdout or a file instead of posting them
+pub struct DryRunSink {
+    output: Option<PathBuf>,
+}
+
+impl DryRunSink {
+    /// Truncates the output file, so that it only contains the messages of this run
+    pub fn new(output: Option<PathBuf>) -> Result<Self> {
+        if let Some(path) = &output {
+            std::fs::File::create(path)
+                .with_context(|| format!("Could not create dry run output {}", path.display()))?;
+        }
+
+        Ok(DryRunSink { output })
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        match &self.output {
+            Some(path) => append_to_file(path, text),
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn write_message(&self, title: &str, message: &Message) -> Result<()> {
+        let mut text = format!(
+            "===== {title} =====\n\
+            {}\n",
+            message.text
+        );
+        if let Some(blocks) = &message.blocks {
+            let blocks =
+                serde_json::to_string_pretty(blocks).context("Failed to serialize blocks")?;
+            text.push_str("----- Blocks -----\n");
+            text.push_str(&blocks);
+            text.push('\n');
+        }
+
+        self.write(&text)
+    }
+}
+
+impl MessageSink for DryRunSink {
+    fn send(
+        &self,
+        _channel: &str,
+        recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>> {
+        self.write_message(&format!("Message to {recipient}"), message)?;
+        Ok(None)
+    }
+
+    fn update(
+        &self,
+        previous: &PostedMessage,
+        recipient: &str,
+        message: &Message,
+    ) -> Result<Option<PostedMessage>> {
+        self.write_message(
+            &format!("Update of message {} to {recipient}", previous.ts),
+            message,
+        )?;
+        Ok(Some(previous.clone()))
+    }
+
+    fn delete(&self, previous: &PostedMessage, recipient: &str) -> Result<()> {
+        self.write(&format!(
+            "===== Deletion of message {} to {recipient} =====\n",
+            previous.ts
+        ))
+    }
+}
+
+fn append_to_file(path: &Path, text: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("Could not open dry run output {}", path.display()))?;
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("Could not write dry run output {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_turns_slack_errors_into_errors() {
+        let response: SlackResponse = serde_json::from_value(serde_json::json!({
+            "ok": false,
+            "error": "channel_not_found",
+            "response_metadata": { "warnings": ["missing_charset"] }
+        }))
+        .unwrap();
+
+        let error = response.into_result().unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<SlackError>(),
+            Some(&SlackError::ChannelNotFound)
+        );
+    }
+
+    #[test]
+    fn it_accepts_ok_responses_with_warnings() {
+        let response: SlackResponse = serde_json::from_value(serde_json::json!({
+            "ok": true,
+            "warning": "superfluous_charset",
+            "channel": "D123",
+            "ts": "1503435956.000247"
+        }))
+        .unwrap();
+
+        assert!(response.into_result().is_ok());
+    }
+
+    #[test]
+    fn it_writes_dry_run_messages_to_file() {
+        let path = std::env::temp_dir().join(format!("{}_dry_run.md", std::process::id()));
+
+        let poster = SlackMessagePoster::with_sink(
+            Box::new(DryRunSink::new(Some(path.clone())).unwrap()),
+            UpdatePolicy::Update,
+        );
+        poster
+            .post_message(
+                &SlackUser("U1".to_string()),
+                &TrelloUser("trello_user1".to_string()),
+                &Message {
+                    text: "Hello".to_string(),
+                    blocks: None,
+                },
+                None,
+            )
+            .unwrap();
+        poster
+            .post_message(
+                &SlackUser("U2".to_string()),
+                &TrelloUser("trello_user2".to_string()),
+                &Message {
+                    text: "World".to_string(),
+                    blocks: Some(serde_json::json!([])),
+                },
+                Some(&PostedMessage {
+                    channel: "D2".to_string(),
+                    ts: "1.0".to_string(),
+                }),
+            )
+            .unwrap();
+
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            output,
+            "===== Message to Slack user U1 (Trello user trello_user1) =====\nHello\n\
+            ===== Update of message 1.0 to Slack user U2 (Trello user trello_user2) =====\nWorld\n\
+            ----- Blocks -----\n[]\n"
+        );
+    }
+
+    /// Records the calls and fails updates of deleted messages
+    #[derive(Default)]
+    struct RecordingSink {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl MessageSink for RecordingSink {
+        fn send(
+            &self,
+            channel: &str,
+            _recipient: &str,
+            _message: &Message,
+        ) -> Result<Option<PostedMessage>> {
+            self.calls.lock().unwrap().push(format!("send {channel}"));
+            Ok(Some(PostedMessage {
+                channel: channel.to_string(),
+                ts: "2.0".to_string(),
+            }))
+        }
+
+        fn update(
+            &self,
+            previous: &PostedMessage,
+            _recipient: &str,
+            _message: &Message,
+        ) -> Result<Option<PostedMessage>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("update {}", previous.ts));
+            if previous.ts == "deleted" {
+                return Err(SlackError::MessageNotFound.into());
+            }
+            Ok(Some(previous.clone()))
+        }
+
+        fn delete(&self, previous: &PostedMessage, _recipient: &str) -> Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("delete {}", previous.ts));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_replaces_the_previous_message_according_to_the_policy() {
+        let post = |policy: UpdatePolicy, previous_ts: &str| {
+            let sink = RecordingSink::default();
+            let calls = std::sync::Arc::clone(&sink.calls);
+            let posted = SlackMessagePoster::with_sink(Box::new(sink), policy)
+                .post_message(
+                    &SlackUser("U1".to_string()),
+                    &TrelloUser("trello_user1".to_string()),
+                    &Message {
+                        text: "Hello".to_string(),
+                        blocks: None,
+                    },
+                    Some(&PostedMessage {
+                        channel: "D1".to_string(),
+                        ts: previous_ts.to_string(),
+                    }),
+                )
+                .unwrap()
+                .unwrap();
+            let calls = calls.lock().unwrap().clone();
+            (calls, posted.ts)
+        };
 
-        Ok(())
+        assert_eq!(
+            post(UpdatePolicy::Post, "1.0"),
+            (vec!["send U1".to_string()], "2.0".to_string())
+        );
+        assert_eq!(
+            post(UpdatePolicy::Update, "1.0"),
+            (vec!["update 1.0".to_string()], "1.0".to_string())
+        );
+        assert_eq!(
+            post(UpdatePolicy::Update, "deleted"),
+            (
+                vec!["update deleted".to_string(), "send U1".to_string()],
+                "2.0".to_string()
+            ),
+            "deleted messages are posted again"
+        );
+        assert_eq!(
+            post(UpdatePolicy::Repost, "1.0"),
+            (
+                vec!["delete 1.0".to_string(), "send U1".to_string()],
+                "2.0".to_string()
+            )
+        );
     }
 }
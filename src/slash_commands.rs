@@ -0,0 +1,210 @@
+use crate::{
+    Recipient, SlackUser, TrelloUser,
+    calendar::WorkCalendar,
+    cards_of, compose_inactive_cards_message, compose_pending_reviews_message,
+    config::AppConfig,
+    fetch_boards, get_inactive_cards, get_pending_reviews,
+    i18n::{Catalog, Locale},
+    inactive_cards_lists,
+    retry::RetryPolicy,
+    review_lists,
+    slack::{Message, SlackDirectory},
+    state::{StateStore, TrackedCard},
+    template::Templates,
+    trello::TrelloClient,
+    user_mapping::UserMappingTable,
+};
+use anyhow::Result;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Slash commands to query the own cards on demand
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// `/trello-reviews`
+    PendingReviews,
+    /// `/trello-stale`
+    InactiveCards,
+}
+
+impl SlashCommand {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "/trello-reviews" => Some(SlashCommand::PendingReviews),
+            "/trello-stale" => Some(SlashCommand::InactiveCards),
+            _ => None,
+        }
+    }
+}
+
+/// Composes the replies to the slash commands, the same messages as the scheduled reminders
+/// restricted to the Trello users mapped to the invoking Slack user
+pub struct SlashCommandHandler {
+    config: AppConfig,
+    trello_client: TrelloClient,
+    slack_directory: SlackDirectory,
+    calendar: WorkCalendar,
+    templates: Templates,
+}
+
+impl SlashCommandHandler {
+    pub fn new(
+        config: &AppConfig,
+        client: reqwest::blocking::Client,
+        catalog: Arc<Catalog>,
+    ) -> Result<Self> {
+        let retry = RetryPolicy::new(&config.retry);
+        Ok(SlashCommandHandler {
+            config: config.clone(),
+            trello_client: TrelloClient::new(client.clone(), retry.clone(), &config.trello),
+            slack_directory: SlackDirectory::new(client, retry, &config.slack),
+            calendar: WorkCalendar::from_config(&config.calendar, config.schedule.timezone)?,
+            templates: Templates::new(&config.templates, catalog)?,
+        })
+    }
+
+    /// Reply to the command of the Slack user. Snoozed cards are left out, but nothing is recorded in the state.
+    pub fn respond(
+        &self,
+        command: SlashCommand,
+        slack_user: &SlackUser,
+        state: &Mutex<StateStore>,
+    ) -> Result<Message> {
+        let config = &self.config;
+        let boards = fetch_boards(config, &self.trello_client)?;
+        let user_mapping =
+            UserMappingTable::resolve(config, &boards.members, &self.slack_directory)?;
+        let mappings: Vec<_> = user_mapping
+            .user_mappings()
+            .filter(|mapping| mapping.slack_user == *slack_user)
+            .collect();
+        let Some(mapping) = mappings.first() else {
+            return Ok(self.text(config.locale, "command-unknown-user"));
+        };
+        let recipient = Recipient {
+            slack_user: slack_user.clone(),
+            locale: mapping.locale.unwrap_or(config.locale),
+        };
+        let trello_users: Vec<&TrelloUser> = mappings
+            .iter()
+            .map(|mapping| &mapping.trello_user)
+            .collect();
+
+        match command {
+            SlashCommand::PendingReviews => {
+                let mut reviews = get_pending_reviews(
+                    &self.trello_client,
+                    &self.calendar,
+                    &config.labels,
+                    &config.unassigned,
+                    &boards.trello_member_id_to_username,
+                    review_lists(config, boards.lists),
+                )?;
+                let reviews =
+                    skip_snoozed(state, slack_user, cards_of(&mut reviews, &trello_users));
+                if reviews.is_empty() {
+                    return Ok(self.text(recipient.locale, "no-pending-reviews"));
+                }
+
+                compose_pending_reviews_message(
+                    &self.templates,
+                    config.slack.message_format,
+                    true,
+                    &recipient,
+                    &mapping.trello_user,
+                    reviews,
+                )
+            }
+            SlashCommand::InactiveCards => {
+                let mut cards = get_inactive_cards(
+                    &self.trello_client,
+                    &self.calendar,
+                    &config.labels,
+                    &config.unassigned,
+                    &boards.trello_member_id_to_username,
+                    inactive_cards_lists(config, boards.lists),
+                )?;
+                let cards = skip_snoozed(state, slack_user, cards_of(&mut cards, &trello_users));
+                if cards.is_empty() {
+                    return Ok(self.text(recipient.locale, "no-inactive-cards"));
+                }
+
+                compose_inactive_cards_message(
+                    &self.templates,
+                    config.slack.message_format,
+                    true,
+                    &self.calendar,
+                    &recipient,
+                    &mapping.trello_user,
+                    cards,
+                )
+            }
+        }
+    }
+
+    fn text(&self, locale: Locale, id: &str) -> Message {
+        Message {
+            text: self.templates.translator(locale).text(id, &[]),
+            blocks: None,
+        }
+    }
+}
+
+fn skip_snoozed<T: TrackedCard>(
+    state: &Mutex<StateStore>,
+    slack_user: &SlackUser,
+    cards: Vec<T>,
+) -> Vec<T> {
+    state
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .skip_snoozed(slack_user, cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PendingReview;
+    use std::collections::HashMap;
+
+    #[test]
+    fn it_merges_the_cards_of_all_mapped_trello_users() {
+        let alice = TrelloUser("alice".to_string());
+        let alice_work = TrelloUser("alice-work".to_string());
+        let bob = TrelloUser("bob".to_string());
+        let mut cards_by_user = HashMap::from([
+            (
+                alice.clone(),
+                vec![
+                    PendingReview::for_test("a", "Review", 0),
+                    PendingReview::for_test("shared", "Review", 0),
+                ],
+            ),
+            (
+                alice_work.clone(),
+                vec![
+                    PendingReview::for_test("shared", "Review", 0),
+                    PendingReview::for_test("b", "Review", 0),
+                ],
+            ),
+            (bob, vec![PendingReview::for_test("c", "Review", 0)]),
+        ]);
+
+        let cards = cards_of(&mut cards_by_user, &[&alice, &alice_work]);
+
+        let card_ids: Vec<&str> = cards.iter().map(|card| card.card_id.as_str()).collect();
+        assert_eq!(card_ids, ["a", "shared", "b"]);
+    }
+
+    #[test]
+    fn it_recognizes_the_commands() {
+        assert_eq!(
+            SlashCommand::from_name("/trello-reviews"),
+            Some(SlashCommand::PendingReviews)
+        );
+        assert_eq!(
+            SlashCommand::from_name("/trello-stale"),
+            Some(SlashCommand::InactiveCards)
+        );
+        assert_eq!(SlashCommand::from_name("/trello"), None);
+    }
+}
@@ -0,0 +1,542 @@
+use crate::{
+    SlackUser,
+    calendar::WorkCalendar,
+    config::{StateConfig, Threshold},
+    slack::PostedMessage,
+};
+use anyhow::{Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+use time::{OffsetDateTime, serde::rfc3339};
+
+/// A card that is part of a notification and can be tracked in the [`StateStore`]
+pub trait TrackedCard {
+    fn card_id(&self) -> &str;
+
+    /// List the card is currently in
+    fn list_id(&self) -> &str;
+
+    /// Marks the card as new since the last reminder
+    fn set_new(&mut self, is_new: bool);
+
+    /// Whether the card is reported on every run, even if it was part of a previous reminder
+    fn is_escalated(&self) -> bool {
+        false
+    }
+}
+
+/// Remembers which cards were reported to which Slack user and when, the messages of the last reminders,
+/// and which cards users have snoozed.
+/// Without a state file, reported cards are not remembered and messages and snoozes only last until the process exits.
+pub struct StateStore {
+    path: Option<PathBuf>,
+    read_only: bool,
+    only_new_cards: bool,
+    renotify_after: Option<Threshold>,
+    state: State,
+}
+
+/// Notified cards per action, Slack user and card ID, last messages per action and Slack user or channel,
+/// and snoozed cards per Slack user and card ID
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    notifications: BTreeMap<String, BTreeMap<String, BTreeMap<String, CardNotification>>>,
+    #[serde(default)]
+    messages: BTreeMap<String, BTreeMap<String, PostedMessage>>,
+    #[serde(default)]
+    snoozes: BTreeMap<String, BTreeMap<String, Snooze>>,
+}
+
+/// Reason to leave a card out of the reminders of a user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Snooze {
+    /// Snoozed until the given time
+    Until {
+        #[serde(with = "rfc3339")]
+        until: OffsetDateTime,
+    },
+    /// The user is working on the card, so it is skipped as long as it stays in the list
+    Acknowledged { list_id: String },
+}
+
+impl Snooze {
+    fn applies(&self, list_id: &str, now: OffsetDateTime) -> bool {
+        match self {
+            Snooze::Until { until } => now < *until,
+            Snooze::Acknowledged {
+                list_id: acknowledged_list_id,
+            } => acknowledged_list_id == list_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CardNotification {
+    #[serde(with = "rfc3339")]
+    first_notified: OffsetDateTime,
+    #[serde(with = "rfc3339")]
+    last_notified: OffsetDateTime,
+}
+
+impl StateStore {
+    pub fn load(config: &StateConfig) -> Result<Self> {
+        let state = match &config.state_file {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Could not read state file {}", path.display()))?;
+                serde_json::from_str(&content)
+                    .with_context(|| format!("Could not parse state file {}", path.display()))?
+            }
+            _ => State::default(),
+        };
+
+        Ok(StateStore {
+            path: config.state_file.clone(),
+            read_only: false,
+            only_new_cards: config.only_new_cards,
+            renotify_after: config.renotify_after,
+            state,
+        })
+    }
+
+    /// Keeps the state file untouched, e.g. for dry runs
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Selects the cards to report to the user and marks cards that were not part of the previous reminder.
+    ///
+    /// Returns an empty list if the user should not be notified at all.
+    pub fn select<T: TrackedCard>(
+        &self,
+        calendar: &WorkCalendar,
+        action: &str,
+        slack_user: &SlackUser,
+        mut cards: Vec<T>,
+    ) -> Vec<T> {
+        if self.path.is_none() {
+            return cards;
+        }
+
+        let Some(notified) = self
+            .state
+            .notifications
+            .get(action)
+            .and_then(|users| users.get(&slack_user.0))
+        else {
+            return cards; // first reminder, so there is nothing to compare with
+        };
+
+        for card in &mut cards {
+            let is_new = !notified.contains_key(card.card_id());
+            card.set_new(is_new);
+        }
+
+        if self.only_new_cards {
+            cards.retain(|card| card.is_escalated() || !notified.contains_key(card.card_id()));
+            return cards;
+        }
+
+        if let Some(renotify_after) = self.renotify_after {
+            let now = OffsetDateTime::now_utc();
+            let renotify_after = calendar.threshold_duration(renotify_after);
+
+            let is_due = cards.iter().any(|card| {
+                card.is_escalated()
+                    || notified.get(card.card_id()).is_none_or(|notification| {
+                        calendar.working_time_between(notification.last_notified, now)
+                            >= renotify_after
+                    })
+            });
+
+            if !is_due {
+                return Vec::new();
+            }
+        }
+
+        cards
+    }
+
+    /// Removes the cards the user has snoozed or acknowledged
+    pub fn skip_snoozed<T: TrackedCard>(
+        &self,
+        slack_user: &SlackUser,
+        mut cards: Vec<T>,
+    ) -> Vec<T> {
+        let Some(snoozes) = self.state.snoozes.get(&slack_user.0) else {
+            return cards;
+        };
+
+        let now = OffsetDateTime::now_utc();
+        cards.retain(|card| {
+            let is_snoozed = snoozes
+                .get(card.card_id())
+                .is_some_and(|snooze| snooze.applies(card.list_id(), now));
+            if is_snoozed {
+                info!(
+                    "Skipping card {} snoozed by Slack user {slack_user}",
+                    card.card_id()
+                );
+            }
+            !is_snoozed
+        });
+
+        cards
+    }
+
+    /// Records a snooze of the user, replacing any previous snooze of the card.
+    /// Expired snoozes of the user are forgotten.
+    pub fn snooze(&mut self, slack_user: &SlackUser, card_id: &str, snooze: Snooze) -> Result<()> {
+        let now = OffsetDateTime::now_utc();
+        let snoozes = self.state.snoozes.entry(slack_user.0.clone()).or_default();
+
+        snoozes.retain(|_, snooze| !matches!(snooze, Snooze::Until { until } if *until <= now));
+        snoozes.insert(card_id.to_string(), snooze);
+
+        self.save()
+    }
+
+    /// Records the reported cards.
+    /// Previously reported cards that are not part of `current_card_ids` anymore are forgotten,
+    /// so the cards of all Trello users of the Slack user must be recorded at once.
+    pub fn record<'c>(
+        &mut self,
+        action: &str,
+        slack_user: &SlackUser,
+        current_card_ids: impl IntoIterator<Item = &'c str>,
+        reported_card_ids: impl IntoIterator<Item = &'c str>,
+    ) -> Result<()> {
+        if self.path.is_none() {
+            return Ok(());
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let reported_card_ids: HashSet<&str> = reported_card_ids.into_iter().collect();
+
+        let previous = self
+            .state
+            .notifications
+            .entry(action.to_string())
+            .or_default()
+            .remove(&slack_user.0)
+            .unwrap_or_default();
+
+        let mut notified = BTreeMap::new();
+        for card_id in current_card_ids {
+            let notification = match (previous.get(card_id), reported_card_ids.contains(card_id)) {
+                (Some(notification), true) => CardNotification {
+                    first_notified: notification.first_notified,
+                    last_notified: now,
+                },
+                (Some(notification), false) => notification.clone(),
+                (None, true) => CardNotification {
+                    first_notified: now,
+                    last_notified: now,
+                },
+                (None, false) => continue,
+            };
+            notified.insert(card_id.to_string(), notification);
+        }
+
+        self.state
+            .notifications
+            .entry(action.to_string())
+            .or_default()
+            .insert(slack_user.0.clone(), notified);
+
+        self.save()
+    }
+
+    /// Last reminder of the action to the Slack user or channel
+    pub fn message(&self, action: &str, recipient: &str) -> Option<&PostedMessage> {
+        self.state.messages.get(action)?.get(recipient)
+    }
+
+    /// Remembers the last reminder of the action, so that it can be updated by the next one.
+    /// `None` forgets the previous reminder.
+    pub fn remember_message(
+        &mut self,
+        action: &str,
+        recipient: &str,
+        message: Option<PostedMessage>,
+    ) -> Result<()> {
+        let messages = self.state.messages.entry(action.to_string()).or_default();
+        match message {
+            Some(message) => messages.insert(recipient.to_string(), message),
+            None => messages.remove(recipient),
+        };
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.read_only {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string_pretty(&self.state)
+            .context("Failed to serialize notification state")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Could not write state file {}", path.display()))?;
+
+        info!("Notification state has been written to {}", path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Weekday;
+
+    #[derive(Debug)]
+    struct Card(&'static str, bool);
+
+    impl TrackedCard for Card {
+        fn card_id(&self) -> &str {
+            self.0
+        }
+
+        fn list_id(&self) -> &'static str {
+            "list"
+        }
+
+        fn set_new(&mut self, is_new: bool) {
+            self.1 = is_new;
+        }
+    }
+
+    /// A card that is escalated if the flag is set
+    struct EscalatedCard(&'static str, bool);
+
+    impl TrackedCard for EscalatedCard {
+        fn card_id(&self) -> &str {
+            self.0
+        }
+
+        fn list_id(&self) -> &'static str {
+            "list"
+        }
+
+        fn set_new(&mut self, _is_new: bool) {}
+
+        fn is_escalated(&self) -> bool {
+            self.1
+        }
+    }
+
+    fn state_store(name: &str, only_new_cards: bool, renotify_after: Option<&str>) -> StateStore {
+        let path = std::env::temp_dir().join(format!("{}_{name}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        StateStore::load(&StateConfig {
+            state_file: Some(path),
+            only_new_cards,
+            renotify_after: renotify_after.map(|threshold| threshold.parse().unwrap()),
+        })
+        .unwrap()
+    }
+
+    fn every_day() -> WorkCalendar {
+        WorkCalendar::new(
+            &[
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+                Weekday::Saturday,
+                Weekday::Sunday,
+            ],
+            [],
+            chrono_tz::Tz::UTC,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_marks_new_cards() {
+        let mut store = state_store("new_cards", false, None);
+        let user = SlackUser("U1".to_string());
+
+        let cards = store.select(&every_day(), "action", &user, vec![Card("a", false)]);
+        assert!(!cards[0].1, "first reminder should not mark cards as new");
+        store.record("action", &user, ["a"], ["a"]).unwrap();
+
+        let cards = store.select(
+            &every_day(),
+            "action",
+            &user,
+            vec![Card("a", false), Card("b", false)],
+        );
+        assert!(!cards[0].1);
+        assert!(cards[1].1);
+
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn it_only_reports_new_cards() {
+        let mut store = state_store("only_new", true, None);
+        let user = SlackUser("U1".to_string());
+
+        store.record("action", &user, ["a"], ["a"]).unwrap();
+
+        let cards = store.select(
+            &every_day(),
+            "action",
+            &user,
+            vec![Card("a", false), Card("b", false)],
+        );
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].0, "b");
+
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn it_waits_before_renotifying() {
+        let mut store = state_store("renotify", false, Some("1d"));
+        let user = SlackUser("U1".to_string());
+
+        store.record("action", &user, ["a"], ["a"]).unwrap();
+
+        let cards = store.select(&every_day(), "action", &user, vec![Card("a", false)]);
+        assert!(cards.is_empty(), "card was reported just now");
+
+        let cards = store.select(
+            &every_day(),
+            "action",
+            &user,
+            vec![Card("a", false), Card("b", false)],
+        );
+        assert_eq!(
+            cards.len(),
+            2,
+            "new cards trigger a reminder with all cards"
+        );
+
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn it_always_reports_escalated_cards() {
+        let mut store = state_store("escalated", true, None);
+        let user = SlackUser("U1".to_string());
+
+        store
+            .record("action", &user, ["a", "b"], ["a", "b"])
+            .unwrap();
+
+        let cards = store.select(
+            &every_day(),
+            "action",
+            &user,
+            vec![EscalatedCard("a", false), EscalatedCard("b", true)],
+        );
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].0, "b");
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+
+        let mut store = state_store("escalated_renotify", false, Some("1d"));
+        store
+            .record("action", &user, ["a", "b"], ["a", "b"])
+            .unwrap();
+        let cards = store.select(
+            &every_day(),
+            "action",
+            &user,
+            vec![EscalatedCard("a", false), EscalatedCard("b", true)],
+        );
+        assert_eq!(cards.len(), 2, "escalated cards trigger a reminder");
+
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn it_skips_snoozed_cards() {
+        let mut store = state_store("snoozes", false, None);
+        let user = SlackUser("U1".to_string());
+        let other_user = SlackUser("U2".to_string());
+        let now = OffsetDateTime::now_utc();
+
+        store
+            .snooze(
+                &user,
+                "a",
+                Snooze::Until {
+                    until: now + time::Duration::days(1),
+                },
+            )
+            .unwrap();
+        store
+            .snooze(
+                &user,
+                "b",
+                Snooze::Until {
+                    until: now - time::Duration::hours(1),
+                },
+            )
+            .unwrap();
+        store
+            .snooze(
+                &user,
+                "c",
+                Snooze::Acknowledged {
+                    list_id: "list".to_string(),
+                },
+            )
+            .unwrap();
+        store
+            .snooze(
+                &user,
+                "d",
+                Snooze::Acknowledged {
+                    list_id: "other list".to_string(),
+                },
+            )
+            .unwrap();
+
+        let cards = vec![
+            Card("a", false),
+            Card("b", false),
+            Card("c", false),
+            Card("d", false),
+        ];
+        let cards = store.skip_snoozed(&user, cards);
+        assert_eq!(
+            cards.iter().map(|card| card.0).collect::<Vec<_>>(),
+            ["b", "d"]
+        );
+
+        let reloaded = StateStore::load(&StateConfig {
+            state_file: store.path.clone(),
+            only_new_cards: false,
+            renotify_after: None,
+        })
+        .unwrap();
+        assert_eq!(
+            reloaded.state.snoozes["U1"].len(),
+            3,
+            "expired snooze is forgotten"
+        );
+        assert_eq!(
+            reloaded
+                .skip_snoozed(&other_user, vec![Card("a", false)])
+                .len(),
+            1
+        );
+
+        std::fs::remove_file(store.path.unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    config::TemplateConfig,
+    i18n::{Arg, Catalog, Locale, Translator},
+};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use log::info;
+use minijinja::{Environment, Error, ErrorKind, State, UndefinedBehavior, Value, value::Kwargs};
+use serde::Serialize;
+#[cfg(test)]
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Message of an action that can be customized with a template
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MessageTemplate {
+    PendingReviews,
+    InactiveCards,
+    PendingReviewsDigest,
+    InactiveCardsDigest,
+    DueDates,
+    BlockedCards,
+    WipLimits,
+    UnassignedCards,
+}
+
+impl MessageTemplate {
+    const ALL: [MessageTemplate; 8] = [
+        MessageTemplate::PendingReviews,
+        MessageTemplate::InactiveCards,
+        MessageTemplate::PendingReviewsDigest,
+        MessageTemplate::InactiveCardsDigest,
+        MessageTemplate::DueDates,
+        MessageTemplate::BlockedCards,
+        MessageTemplate::WipLimits,
+        MessageTemplate::UnassignedCards,
+    ];
+
+    /// Name of the template file in the template directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            MessageTemplate::PendingReviews => "pending-reviews.md",
+            MessageTemplate::InactiveCards => "inactive-cards.md",
+            MessageTemplate::PendingReviewsDigest => "pending-reviews-digest.md",
+            MessageTemplate::InactiveCardsDigest => "inactive-cards-digest.md",
+            MessageTemplate::DueDates => "due-dates.md",
+            MessageTemplate::BlockedCards => "blocked-cards.md",
+            MessageTemplate::WipLimits => "wip-limits.md",
+            MessageTemplate::UnassignedCards => "unassigned-cards.md",
+        }
+    }
+
+    fn default_source(self) -> &'static str {
+        match self {
+            MessageTemplate::PendingReviews => include_str!("../templates/pending-reviews.md"),
+            MessageTemplate::InactiveCards => include_str!("../templates/inactive-cards.md"),
+            MessageTemplate::PendingReviewsDigest => {
+                include_str!("../templates/pending-reviews-digest.md")
+            }
+            MessageTemplate::InactiveCardsDigest => {
+                include_str!("../templates/inactive-cards-digest.md")
+            }
+            MessageTemplate::DueDates => include_str!("../templates/due-dates.md"),
+            MessageTemplate::BlockedCards => include_str!("../templates/blocked-cards.md"),
+            MessageTemplate::WipLimits => include_str!("../templates/wip-limits.md"),
+            MessageTemplate::UnassignedCards => include_str!("../templates/unassigned-cards.md"),
+        }
+    }
+}
+
+/// Renders the messages of the actions with `MiniJinja` templates.
+///
+/// Templates are looked up in the template directory and fall back to the built-in templates.
+/// They can translate texts of the message catalog with `t("message-id", arg=value)`.
+pub struct Templates {
+    environment: Environment<'static>,
+    catalog: Arc<Catalog>,
+}
+
+impl Templates {
+    pub fn new(config: &TemplateConfig, catalog: Arc<Catalog>) -> Result<Self> {
+        let mut environment = Environment::new();
+        environment.set_trim_blocks(true);
+        environment.set_lstrip_blocks(true);
+        environment.set_keep_trailing_newline(true);
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+
+        let translations = Arc::clone(&catalog);
+        environment.add_function(
+            "t",
+            move |state: &State, id: &str, kwargs: Kwargs| -> Result<String, Error> {
+                translate(&translations, state, id, &kwargs)
+            },
+        );
+
+        for template in MessageTemplate::ALL {
+            let custom_file = config
+                .template_dir
+                .as_ref()
+                .map(|dir| dir.join(template.file_name()))
+                .filter(|path| path.exists());
+
+            let source = match &custom_file {
+                Some(path) => {
+                    info!("Using message template {}", path.display());
+                    std::fs::read_to_string(path).with_context(|| {
+                        format!("Could not read message template {}", path.display())
+                    })?
+                }
+                None => template.default_source().to_string(),
+            };
+
+            environment
+                .add_template_owned(template.file_name(), source)
+                .with_context(|| format!("Invalid message template {}", template.file_name()))?;
+        }
+
+        Ok(Templates {
+            environment,
+            catalog,
+        })
+    }
+
+    pub fn translator(&self, locale: Locale) -> &Translator {
+        self.catalog.translator(locale)
+    }
+
+    /// Renders the template, providing the `locale` in addition to the `context`
+    pub fn render(
+        &self,
+        template: MessageTemplate,
+        locale: Locale,
+        context: impl Serialize,
+    ) -> Result<String> {
+        let context = minijinja::context! {
+            locale => locale.to_string(),
+            ..Value::from_serialize(context)
+        };
+
+        self.environment
+            .get_template(template.file_name())
+            .and_then(|compiled| compiled.render(context))
+            .with_context(|| format!("Failed to render message template {}", template.file_name()))
+    }
+}
+
+#[cfg(test)]
+impl Templates {
+    /// Templates with the built-in translations, e.g. to compose messages
+    pub(crate) fn for_test(template_dir: Option<PathBuf>) -> Self {
+        Templates::new(&TemplateConfig { template_dir }, Arc::new(Catalog::new())).unwrap()
+    }
+}
+
+/// Implementation of the `t` template function
+fn translate(catalog: &Catalog, state: &State, id: &str, kwargs: &Kwargs) -> Result<String, Error> {
+    let locale = state
+        .lookup("locale")
+        .and_then(|locale| Locale::from_str(locale.as_str()?, true).ok())
+        .unwrap_or_default();
+
+    let translator = catalog.translator(locale);
+    if !translator.has_message(id) {
+        return Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("unknown message `{id}`"),
+        ));
+    }
+
+    let mut args = Vec::new();
+    for name in kwargs.args() {
+        let value: Value = kwargs.get(name)?;
+        let arg = match i64::try_from(value.clone()) {
+            Ok(number) => Arg::Number(number),
+            Err(_) => Arg::Text(value.to_string()),
+        };
+        args.push((name, arg));
+    }
+
+    Ok(translator.text(id, &args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_the_built_in_templates() {
+        let text = Templates::for_test(None)
+            .render(
+                MessageTemplate::PendingReviews,
+                Locale::De,
+                serde_json::json!({
+                    "count": 2,
+                    "reviews": [
+                        { "card_name": "A", "card_url": "https://trello.com/c/a", "pending_since_days": 3, "is_new": true, "is_urgent": true },
+                        { "card_name": "B", "card_url": "https://trello.com/c/b", "pending_since_days": 0, "is_new": false, "is_urgent": false },
+                    ],
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(
+            text,
+            "**🔎 Du hast 2 ausstehende Reviews:**\n\
+            - [A](https://trello.com/c/a) 🆕 ❗ - Wartet seit 3 Tagen 🚨🚨\n\
+            - [B](https://trello.com/c/b)\n\
+            \n\n\n\
+            Mach das Team glücklich und bearbeite das zeitnah!\n"
+        );
+    }
+
+    #[test]
+    fn it_prefers_templates_of_the_template_directory() {
+        let dir = std::env::temp_dir().join(format!("{}_templates", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("inactive-cards.md"),
+            "{{ count }} {{ t(\"inactive-cards-closing\") }}",
+        )
+        .unwrap();
+
+        let templates = Templates::for_test(Some(dir.clone()));
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let text = templates
+            .render(
+                MessageTemplate::InactiveCards,
+                Locale::En,
+                serde_json::json!({ "count": 1, "cards": [] }),
+            )
+            .unwrap();
+        assert_eq!(
+            text,
+            "1 Please check whether the cards still need some work!"
+        );
+
+        let missing_variable = templates.render(
+            MessageTemplate::InactiveCards,
+            Locale::En,
+            serde_json::json!({ "cards": [] }),
+        );
+        assert!(missing_variable.is_err());
+    }
+}
@@ -1,35 +1,50 @@
 use crate::{
     config::TrelloConfig,
-    schema::{ActionType, Card, List, Member},
+    retry::RetryPolicy,
+    schema::{Action, ActionType, Board, Card, List, Member},
     util::{debug_write_to_file, is_sorted_descending},
 };
 use anyhow::{Context, Result, bail};
 use reqwest::header::ACCEPT;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Maximum number of actions Trello returns per request
+const ACTIONS_PAGE_SIZE: usize = 1000;
 
 pub struct TrelloClient {
     client: reqwest::blocking::Client,
+    retry: RetryPolicy,
+    api_url: String,
     key: String,
     token: String,
 }
 
 impl TrelloClient {
-    pub fn new(client: reqwest::blocking::Client, config: &TrelloConfig) -> Self {
+    pub fn new(
+        client: reqwest::blocking::Client,
+        retry: RetryPolicy,
+        config: &TrelloConfig,
+    ) -> Self {
         TrelloClient {
             client,
+            retry,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
             key: config.key.clone(),
             token: config.token.clone(),
         }
     }
 
     pub fn get_members(&self, board_id: &str) -> Result<Vec<Member>> {
-        let response = self
-            .client
-            .get(format!(
-                "https://api.trello.com/1/boards/{board_id}/members"
-            ))
-            .query(&[("key", &self.key), ("token", &self.token)])
-            .header(ACCEPT, "application/json")
-            .send()?;
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/boards/{board_id}/members", self.api_url))
+                .query(&[
+                    ("key", self.key.as_ref()),
+                    ("token", self.token.as_ref()),
+                    ("fields", "username,fullName,email"),
+                ])
+                .header(ACCEPT, "application/json"),
+        )?;
 
         if !response.status().is_success() {
             bail!("Failed to send message: {:?}", response.text()?);
@@ -44,18 +59,42 @@ impl TrelloClient {
         Ok(members)
     }
 
+    pub fn get_board(&self, board_id: &str) -> Result<Board> {
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/boards/{board_id}", self.api_url))
+                .query(&[
+                    ("key", self.key.as_ref()),
+                    ("token", self.token.as_ref()),
+                    ("fields", "id,name,shortLink"),
+                ])
+                .header(ACCEPT, "application/json"),
+        )?;
+
+        if !response.status().is_success() {
+            bail!("Failed to fetch board: {:?}", response.text()?);
+        }
+
+        let json: serde_json::Value = response.json()?;
+
+        debug_write_to_file(&json, &format!("debug/board_{board_id}.json"), "Board")?;
+
+        let board: Board = serde_json::from_value(json).context("Could not parse JSON response")?;
+        Ok(board)
+    }
+
     pub fn get_lists(&self, board_id: &str) -> Result<Vec<List>> {
-        let response = self
-            .client
-            .get(format!("https://api.trello.com/1/boards/{board_id}/lists"))
-            .query(&[
-                ("key", self.key.as_ref()),
-                ("token", self.token.as_ref()),
-                ("cards", "none"),
-                ("fields", "id,name"),
-            ])
-            .header(ACCEPT, "application/json")
-            .send()?;
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/boards/{board_id}/lists", self.api_url))
+                .query(&[
+                    ("key", self.key.as_ref()),
+                    ("token", self.token.as_ref()),
+                    ("cards", "none"),
+                    ("fields", "id,name"),
+                ])
+                .header(ACCEPT, "application/json"),
+        )?;
 
         if !response.status().is_success() {
             bail!("Failed to send message: {:?}", response.text()?);
@@ -71,17 +110,20 @@ impl TrelloClient {
     }
 
     pub fn get_cards(&self, list_id: &str) -> Result<Vec<Card>> {
-        let response = self
-            .client
-            .get(format!("https://api.trello.com/1/lists/{list_id}/cards"))
-            .query(&[
-                ("key", self.key.as_ref()),
-                ("token", self.token.as_ref()),
-                ("fields", "name,idList,idMembers,dateLastActivity,url"),
-                ("actions", "updateCard:idList,createCard"),
-            ])
-            .header(ACCEPT, "application/json")
-            .send()?;
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/lists/{list_id}/cards", self.api_url))
+                .query(&[
+                    ("key", self.key.as_ref()),
+                    ("token", self.token.as_ref()),
+                    (
+                        "fields",
+                        "name,idList,idMembers,dateLastActivity,url,due,dueComplete,labels",
+                    ),
+                    ("actions", "updateCard:idList,createCard"),
+                ])
+                .header(ACCEPT, "application/json"),
+        )?;
 
         if !response.status().is_success() {
             bail!("Failed to send message: {:?}", response.text()?);
@@ -96,6 +138,106 @@ impl TrelloClient {
         cards.sort_by_key(|card| card.actions.first().map(|action| action.date));
         Ok(cards)
     }
+
+    /// All cards of a board including archived ones, without their actions
+    pub fn get_board_cards(&self, board_id: &str) -> Result<Vec<Card>> {
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/boards/{board_id}/cards", self.api_url))
+                .query(&[
+                    ("key", self.key.as_ref()),
+                    ("token", self.token.as_ref()),
+                    ("filter", "all"),
+                    ("fields", "name,idList,idMembers,dateLastActivity,url"),
+                ])
+                .header(ACCEPT, "application/json"),
+        )?;
+
+        if !response.status().is_success() {
+            bail!("Failed to fetch cards: {:?}", response.text()?);
+        }
+
+        let json: serde_json::Value = response.json()?;
+
+        debug_write_to_file(
+            &json,
+            &format!("debug/board_cards_{board_id}.json"),
+            "Cards",
+        )?;
+
+        let cards: Vec<Card> =
+            serde_json::from_value(json).context("Could not parse JSON response")?;
+        Ok(cards)
+    }
+
+    /// List moves and creations of the cards of a board since the given date, newest first
+    pub fn get_board_actions(&self, board_id: &str, since: OffsetDateTime) -> Result<Vec<Action>> {
+        let since = since.format(&Rfc3339)?;
+
+        paginate(
+            ACTIONS_PAGE_SIZE,
+            |action: &Action| &action.id,
+            |before| self.get_board_actions_page(board_id, &since, before),
+        )
+    }
+
+    fn get_board_actions_page(
+        &self,
+        board_id: &str,
+        since: &str,
+        before: Option<&str>,
+    ) -> Result<Vec<Action>> {
+        let limit = ACTIONS_PAGE_SIZE.to_string();
+        let mut query = vec![
+            ("key", self.key.as_str()),
+            ("token", self.token.as_str()),
+            ("filter", "updateCard:idList,createCard"),
+            ("limit", &limit),
+            ("since", since),
+        ];
+        if let Some(before) = before {
+            query.push(("before", before));
+        }
+
+        let response = self.retry.send(
+            self.client
+                .get(format!("{}/boards/{board_id}/actions", self.api_url))
+                .query(&query)
+                .header(ACCEPT, "application/json"),
+        )?;
+
+        if !response.status().is_success() {
+            bail!("Failed to fetch actions: {:?}", response.text()?);
+        }
+
+        let json: serde_json::Value = response.json()?;
+
+        debug_write_to_file(&json, &format!("debug/actions_{board_id}.json"), "Actions")?;
+
+        let actions: Vec<Action> =
+            serde_json::from_value(json).context("Could not parse JSON response")?;
+        Ok(actions)
+    }
+}
+
+/// Fetches pages, newest first, until a page is not full.
+/// Each further page is requested `before` the ID of the oldest item so far.
+fn paginate<T>(
+    page_size: usize,
+    id: impl Fn(&T) -> &str,
+    mut fetch_page: impl FnMut(Option<&str>) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    let mut items: Vec<T> = Vec::new();
+
+    loop {
+        let page = fetch_page(items.last().map(&id))?;
+        let is_last_page = page.len() < page_size || page.is_empty();
+        items.extend(page);
+
+        if is_last_page {
+            return Ok(items);
+        }
+    }
 }
 
 pub fn last_update_from_card(card: &Card) -> time::OffsetDateTime {
@@ -152,6 +294,27 @@ mod tests {
     use super::*;
     use time::{OffsetDateTime, macros::datetime};
 
+    #[test]
+    fn it_fetches_pages_before_the_oldest_item() {
+        let items: Vec<String> = (0..5).rev().map(|i| format!("a{i}")).collect();
+        let mut requested = Vec::new();
+
+        let fetched = paginate(2, String::as_str, |before: Option<&str>| {
+            requested.push(before.map(ToString::to_string));
+            let start = before.map_or(0, |before| {
+                items.iter().position(|item| item == before).unwrap() + 1
+            });
+            Ok(items[start..(start + 2).min(items.len())].to_vec())
+        })
+        .unwrap();
+
+        assert_eq!(fetched, items);
+        assert_eq!(
+            requested,
+            [None, Some("a3".to_string()), Some("a1".to_string())]
+        );
+    }
+
     #[test]
     fn it_extracts_creation_date_from_card_id() {
         let date = creation_date_from_card_id("4d5ea62fd76aa1136000000c").unwrap();
@@ -0,0 +1,248 @@
+use crate::{
+    ActionContext, BoardList, InactiveCard, InactiveCardContext, Recipient, TrelloUser,
+    calendar::WorkCalendar,
+    card_members,
+    config::{ActionConfig, LabelConfig, Threshold, UnassignedConfig, board_channel},
+    i18n::Locale,
+    post_digest,
+    slack::{DeliveryReport, Message},
+    state::StateStore,
+    template::{MessageTemplate, Templates},
+    trello::{TrelloClient, moved_to_list_date},
+};
+use anyhow::{Result, bail};
+use log::info;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// List with cards without members, together with the owner they are routed to
+#[derive(Clone, Debug)]
+pub struct UnassignedList {
+    board_id: String,
+    board_name: String,
+    list_name: String,
+    owner: Option<TrelloUser>,
+    cards: Vec<InactiveCard>,
+}
+
+/// ACTION: Post the cards without members of the watched lists to the channel of their board
+pub fn unassigned_cards(
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = (BoardList, Threshold)>,
+    channel: Option<&String>,
+) -> Result<()> {
+    let ActionContext {
+        trello_client,
+        slack_poster,
+        slack_directory: _,
+        calendar,
+        templates,
+        message_format: _,
+        snooze_buttons: _,
+        locale,
+        digest: _,
+        labels,
+        unassigned,
+        boards,
+    } = *context;
+
+    // the channels are resolved before fetching the cards, so that a missing channel is noticed right away
+    let target_lists: Vec<_> = target_lists.collect();
+    let mut channels = HashMap::new();
+    for (board_list, _) in &target_lists {
+        let Some(channel) = board_channel(
+            boards,
+            &board_list.board_id,
+            |channels| &channels.unassigned_cards,
+            channel,
+        ) else {
+            bail!(
+                "No unassigned cards channel configured for board {}, set UNASSIGNED_CARDS_CHANNEL or the channel of the board",
+                board_list.board_name
+            );
+        };
+        channels.insert(board_list.board_id.clone(), channel);
+    }
+
+    let lists = get_unassigned_cards(
+        trello_client,
+        calendar,
+        labels,
+        unassigned,
+        trello_member_id_to_username,
+        target_lists,
+    )?;
+    if lists.is_empty() {
+        info!("No cards without members, skipping notification");
+        return Ok(());
+    }
+
+    let mut lists_by_channel = BTreeMap::<&String, Vec<UnassignedList>>::new();
+    for list in lists {
+        lists_by_channel
+            .entry(channels[&list.board_id])
+            .or_default()
+            .push(list);
+    }
+
+    let action = ActionConfig::UnassignedCards.to_string();
+    let mut report = DeliveryReport::default();
+    let mut alerts = Vec::new();
+    for (channel, lists) in lists_by_channel {
+        let message = compose_unassigned_cards_message(
+            templates,
+            calendar,
+            locale,
+            lists,
+            trello_to_slack_mapping,
+        )?;
+        alerts.push((channel, message));
+    }
+    post_digest(slack_poster, state, &mut report, &action, alerts)?;
+
+    report.finish()
+}
+
+/// Lists with cards without any members on the boards, the cards are ordered oldest first
+fn get_unassigned_cards(
+    trello_client: &TrelloClient,
+    calendar: &WorkCalendar,
+    labels: &LabelConfig,
+    unassigned: &UnassignedConfig,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: Vec<(BoardList, Threshold)>,
+) -> Result<Vec<UnassignedList>> {
+    let mut lists = Vec::new();
+
+    for (board_list, threshold) in target_lists {
+        let BoardList {
+            board_id,
+            board_name,
+            list,
+        } = &board_list;
+        info!("Processing list '{}' (ID: {})", list.name, list.id);
+
+        let cards = trello_client.get_cards(&list.id)?;
+
+        let mut unassigned_cards = cards
+            .iter()
+            // like `card_assignees`, members who left the boards do not count
+            .filter(|card| {
+                labels.includes(card) && card_members(card, trello_member_id_to_username).is_empty()
+            })
+            .map(|card| {
+                Ok(InactiveCard {
+                    card_id: card.id.clone(),
+                    card_name: card.name.clone(),
+                    card_url: card.url.clone(),
+                    board_id: board_id.clone(),
+                    board_name: board_name.clone(),
+                    list_id: list.id.clone(),
+                    list_name: list.name.clone(),
+                    pending_since: calendar.working_time_since(moved_to_list_date(card)?),
+                    threshold: calendar.threshold_duration(threshold),
+                    is_new: false,
+                    is_urgent: labels.is_urgent(card),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if unassigned_cards.is_empty() {
+            continue;
+        }
+        unassigned_cards.sort_by_key(|card| std::cmp::Reverse(card.pending_since)); // descending
+
+        lists.push(UnassignedList {
+            board_id: board_id.clone(),
+            board_name: board_name.clone(),
+            list_name: list.name.clone(),
+            owner: unassigned.owner(&board_list).cloned(),
+            cards: unassigned_cards,
+        });
+    }
+
+    Ok(lists)
+}
+
+#[derive(Serialize)]
+struct UnassignedCardsContext {
+    count: usize,
+    lists: Vec<UnassignedListContext>,
+}
+
+#[derive(Serialize)]
+struct UnassignedListContext {
+    board_name: String,
+    list_name: String,
+    /// Owner of the list, if any
+    trello_user: Option<String>,
+    /// Only set for mapped owners
+    slack_user: Option<String>,
+    cards: Vec<InactiveCardContext>,
+}
+
+pub fn compose_unassigned_cards_message(
+    templates: &Templates,
+    calendar: &WorkCalendar,
+    locale: Locale,
+    lists: Vec<UnassignedList>,
+    recipients: &HashMap<TrelloUser, Recipient>,
+) -> Result<Message> {
+    let translator = templates.translator(locale);
+    let lists: Vec<UnassignedListContext> = lists
+        .into_iter()
+        .map(|list| UnassignedListContext {
+            slack_user: list
+                .owner
+                .as_ref()
+                .and_then(|owner| recipients.get(owner))
+                .map(|recipient| recipient.slack_user.0.clone()),
+            trello_user: list.owner.map(|owner| owner.0),
+            board_name: list.board_name,
+            list_name: list.list_name,
+            cards: list
+                .cards
+                .into_iter()
+                .map(|card| InactiveCardContext::new(translator, calendar, card))
+                .collect(),
+        })
+        .collect();
+
+    let text = templates.render(
+        MessageTemplate::UnassignedCards,
+        locale,
+        UnassignedCardsContext {
+            count: lists.iter().map(|list| list.cards.len()).sum(),
+            lists,
+        },
+    )?;
+
+    Ok(Message { text, blocks: None })
+}
+
+/// Lists of the sample cards, one of them with an owner, e.g. to render the template
+pub fn sample_unassigned_lists(
+    owner: &TrelloUser,
+    mut cards: Vec<InactiveCard>,
+) -> Vec<UnassignedList> {
+    let review_cards = cards.split_off(1);
+
+    vec![
+        UnassignedList {
+            board_id: "sample".to_string(),
+            board_name: "Sample board".to_string(),
+            list_name: "In Progress".to_string(),
+            owner: None,
+            cards,
+        },
+        UnassignedList {
+            board_id: "sample".to_string(),
+            board_name: "Sample board".to_string(),
+            list_name: "Review".to_string(),
+            owner: Some(owner.clone()),
+            cards: review_cards,
+        },
+    ]
+}
@@ -0,0 +1,350 @@
+use crate::{
+    SlackUser, TrelloUser,
+    config::{AppConfig, UserMapping},
+    schema::Member,
+    slack::{SlackDirectory, SlackMember},
+};
+use anyhow::Result;
+use log::warn;
+use std::fmt::Display;
+
+/// How a Trello user was mapped to their Slack user
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappingSource {
+    /// Configured in the user mapping
+    Manual,
+    /// Same email address in Trello and Slack
+    Email,
+    /// Same full name in Trello and Slack
+    Name,
+}
+
+impl Display for MappingSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MappingSource::Manual => write!(f, "manual"),
+            MappingSource::Email => write!(f, "email"),
+            MappingSource::Name => write!(f, "name"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolvedMapping {
+    pub mapping: UserMapping,
+    /// Full name of the Trello member, unknown for manual mappings of users that are no board members
+    pub full_name: Option<String>,
+    pub source: MappingSource,
+}
+
+/// Trello member without a Slack user
+#[derive(Clone, Debug)]
+pub struct UnmatchedMember {
+    pub trello_user: TrelloUser,
+    pub full_name: String,
+    /// Slack users with the same name, if the name is ambiguous
+    pub candidates: Vec<SlackUser>,
+}
+
+/// The manual user mapping, completed by the automatic mapping of the remaining board members if enabled
+#[derive(Clone, Debug, Default)]
+pub struct UserMappingTable {
+    pub mappings: Vec<ResolvedMapping>,
+    pub unmatched: Vec<UnmatchedMember>,
+}
+
+enum SlackMatch {
+    Found(SlackUser, MappingSource),
+    Ambiguous(Vec<SlackUser>),
+    None,
+}
+
+impl UserMappingTable {
+    /// Resolves the mapping of the board members. The Slack users are only fetched for the automatic mapping.
+    pub fn resolve(
+        config: &AppConfig,
+        members: &[Member],
+        directory: &SlackDirectory,
+    ) -> Result<Self> {
+        let slack_members = if config.auto_user_mapping {
+            Some(directory.members()?)
+        } else {
+            None
+        };
+
+        Ok(Self::new(
+            &config.user_mapping,
+            members,
+            slack_members.as_deref(),
+        ))
+    }
+
+    /// Manual mappings take precedence, the other members are matched by email address, then by full name
+    fn new(
+        user_mapping: &[UserMapping],
+        members: &[Member],
+        slack_members: Option<&[SlackMember]>,
+    ) -> Self {
+        let mut members: Vec<&Member> = members.iter().collect();
+        members.sort_by(|a, b| a.username.cmp(&b.username));
+
+        let full_name = |trello_user: &TrelloUser| {
+            members
+                .iter()
+                .find(|member| member.username == trello_user.0)
+                .map(|member| member.full_name.clone())
+        };
+
+        let mut table = UserMappingTable {
+            mappings: user_mapping
+                .iter()
+                .map(|mapping| ResolvedMapping {
+                    mapping: mapping.clone(),
+                    full_name: full_name(&mapping.trello_user),
+                    source: MappingSource::Manual,
+                })
+                .collect(),
+            unmatched: Vec::new(),
+        };
+
+        for member in members {
+            if user_mapping
+                .iter()
+                .any(|mapping| mapping.trello_user.0 == member.username)
+            {
+                continue;
+            }
+
+            let trello_user = TrelloUser(member.username.clone());
+            match slack_members.map_or(SlackMatch::None, |slack_members| {
+                find_slack_user(member, slack_members)
+            }) {
+                SlackMatch::Found(slack_user, source) => table.mappings.push(ResolvedMapping {
+                    mapping: UserMapping {
+                        trello_user,
+                        slack_user,
+                        locale: None,
+                    },
+                    full_name: Some(member.full_name.clone()),
+                    source,
+                }),
+                SlackMatch::Ambiguous(candidates) => {
+                    warn!(
+                        "Trello user {trello_user} matches several Slack users by name, add it to the user mapping"
+                    );
+                    table.unmatched.push(UnmatchedMember {
+                        trello_user,
+                        full_name: member.full_name.clone(),
+                        candidates,
+                    });
+                }
+                SlackMatch::None => table.unmatched.push(UnmatchedMember {
+                    trello_user,
+                    full_name: member.full_name.clone(),
+                    candidates: Vec::new(),
+                }),
+            }
+        }
+
+        table
+    }
+
+    pub fn user_mappings(&self) -> impl Iterator<Item = &UserMapping> {
+        self.mappings.iter().map(|resolved| &resolved.mapping)
+    }
+
+    /// Plain text tables of the mappings and the unmatched members
+    pub fn render(&self) -> String {
+        use tabled::{builder::Builder, settings::Style};
+
+        let mut builder = Builder::with_capacity(self.mappings.len() + 1, 4);
+        builder.push_record(["Trello User", "Name", "Slack User", "Source"]);
+        for resolved in &self.mappings {
+            builder.push_record([
+                resolved.mapping.trello_user.0.clone(),
+                resolved.full_name.clone().unwrap_or_default(),
+                resolved.mapping.slack_user.0.clone(),
+                resolved.source.to_string(),
+            ]);
+        }
+        let mut table = builder.build();
+        table.with(Style::modern());
+
+        let mut output = format!("{table}\n");
+        if self.unmatched.is_empty() {
+            return output;
+        }
+
+        output.push_str("\nTrello members without a Slack user:\n");
+        for member in &self.unmatched {
+            let mut line = format!("- {} ({})", member.trello_user, member.full_name);
+            if !member.candidates.is_empty() {
+                let candidates: Vec<&str> = member
+                    .candidates
+                    .iter()
+                    .map(|slack_user| slack_user.0.as_str())
+                    .collect();
+                line.push_str(", ambiguous name: ");
+                line.push_str(&candidates.join(", "));
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+fn find_slack_user(member: &Member, slack_members: &[SlackMember]) -> SlackMatch {
+    let active = || {
+        slack_members
+            .iter()
+            .filter(|slack_member| !slack_member.deleted && !slack_member.is_bot)
+    };
+
+    if let Some(email) = member.email.as_deref().filter(|email| !email.is_empty())
+        && let Some(slack_member) = active().find(|slack_member| {
+            slack_member
+                .profile
+                .email
+                .as_deref()
+                .is_some_and(|slack_email| slack_email.eq_ignore_ascii_case(email))
+        })
+    {
+        return SlackMatch::Found(SlackUser(slack_member.id.clone()), MappingSource::Email);
+    }
+
+    let name = normalize_name(&member.full_name);
+    if name.is_empty() {
+        return SlackMatch::None;
+    }
+
+    let mut candidates: Vec<SlackUser> = active()
+        .filter(|slack_member| {
+            [
+                slack_member.real_name.as_deref(),
+                slack_member.profile.real_name.as_deref(),
+                slack_member.profile.display_name.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|slack_name| normalize_name(slack_name) == name)
+        })
+        .map(|slack_member| SlackUser(slack_member.id.clone()))
+        .collect();
+
+    match candidates.len() {
+        0 => SlackMatch::None,
+        1 => SlackMatch::Found(candidates.remove(0), MappingSource::Name),
+        _ => SlackMatch::Ambiguous(candidates),
+    }
+}
+
+/// Case and whitespace insensitive form of a name
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack::SlackProfile;
+
+    fn member(username: &str, full_name: &str, email: Option<&str>) -> Member {
+        Member {
+            id: username.to_string(),
+            username: username.to_string(),
+            full_name: full_name.to_string(),
+            email: email.map(ToString::to_string),
+        }
+    }
+
+    fn slack_member(id: &str, real_name: &str, email: Option<&str>) -> SlackMember {
+        SlackMember {
+            id: id.to_string(),
+            deleted: false,
+            is_bot: false,
+            real_name: Some(real_name.to_string()),
+            profile: SlackProfile {
+                email: email.map(ToString::to_string),
+                real_name: Some(real_name.to_string()),
+                display_name: None,
+            },
+        }
+    }
+
+    #[test]
+    fn it_maps_by_email_then_by_name_after_the_manual_mapping() {
+        let user_mapping = [UserMapping {
+            trello_user: TrelloUser("alice".to_string()),
+            slack_user: SlackUser("U_MANUAL".to_string()),
+            locale: None,
+        }];
+        let members = [
+            member("alice", "Alice Adams", Some("alice@example.com")),
+            member("bob", "Bob Brown", Some("BOB@example.com")),
+            member("carol", "carol  clark", None),
+            member("dave", "Dave Davis", None),
+        ];
+        let mut deleted = slack_member("U_OLD_CAROL", "Carol Clark", None);
+        deleted.deleted = true;
+        let slack_members = [
+            slack_member("U_ALICE", "Alice Adams", Some("alice@example.com")),
+            slack_member("U_BOB", "Robert Brown", Some("bob@example.com")),
+            slack_member("U_CAROL", "Carol Clark", None),
+            deleted,
+        ];
+
+        let table = UserMappingTable::new(&user_mapping, &members, Some(&slack_members));
+
+        let mappings: Vec<(&str, &str, MappingSource)> = table
+            .mappings
+            .iter()
+            .map(|resolved| {
+                (
+                    resolved.mapping.trello_user.0.as_str(),
+                    resolved.mapping.slack_user.0.as_str(),
+                    resolved.source,
+                )
+            })
+            .collect();
+        assert_eq!(
+            mappings,
+            [
+                ("alice", "U_MANUAL", MappingSource::Manual),
+                ("bob", "U_BOB", MappingSource::Email),
+                ("carol", "U_CAROL", MappingSource::Name),
+            ]
+        );
+        assert_eq!(table.unmatched.len(), 1);
+        assert_eq!(table.unmatched[0].trello_user.0, "dave");
+    }
+
+    #[test]
+    fn it_leaves_ambiguous_names_unmatched() {
+        let members = [member("carol", "Carol", None)];
+        let slack_members = [
+            slack_member("U_CAROL1", "Carol", None),
+            slack_member("U_CAROL2", "carol", None),
+        ];
+
+        let table = UserMappingTable::new(&[], &members, Some(&slack_members));
+
+        assert!(table.mappings.is_empty());
+        assert_eq!(
+            table.unmatched[0].candidates,
+            [
+                SlackUser("U_CAROL1".to_string()),
+                SlackUser("U_CAROL2".to_string())
+            ]
+        );
+        assert!(
+            table
+                .render()
+                .contains("ambiguous name: U_CAROL1, U_CAROL2")
+        );
+    }
+}
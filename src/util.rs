@@ -1,4 +1,7 @@
-use crate::{config::AppConfig, schema::Action};
+use crate::{
+    config::{AppConfig, ListLimit},
+    schema::Action,
+};
 use anyhow::{Context, Result};
 use log::info;
 use std::io::Write;
@@ -35,22 +38,71 @@ pub fn debug_write_to_file<T: serde::Serialize>(
 pub fn print_summary(config: &AppConfig) {
     use tabled::{builder::Builder, settings::Style};
 
-    let mut builder = Builder::with_capacity(5, 2);
+    let mut builder = Builder::with_capacity(15, 2);
+    builder.push_record(["Users", &users_summary(config)]);
+    builder.push_record(["Trello Boards", &config.trello.board_ids.join("\n")]);
+    builder.push_record(["Review Lists", &join(&config.trello.review_lists, "\n")]);
     builder.push_record([
-        "Users",
+        "Inactive Cards Lists",
+        &join(&config.trello.inactive_cards_lists, "\n"),
+    ]);
+    builder.push_record([
+        "Inactive Threshold",
+        &config.trello.inactive_threshold.to_string(),
+    ]);
+    builder.push_record([
+        "Inactive List Thresholds",
         &config
-            .user_mapping
+            .trello
+            .inactive_list_thresholds
             .iter()
-            .map(|m| m.trello_user.0.clone())
+            .map(|list_threshold| format!("{}: {}", list_threshold.list, list_threshold.threshold))
             .collect::<Vec<_>>()
             .join("\n"),
     ]);
-    builder.push_record(["Trello Boards", &config.trello.board_ids.join("\n")]);
-    builder.push_record(["Review Lists", &config.trello.review_lists.join("\n")]);
+    builder.push_record(["Due Dates", &due_dates_summary(config)]);
+    builder.push_record(["Labels", &labels_summary(config)]);
+    builder.push_record(["WIP Limits", &wip_limits_summary(config)]);
+    builder.push_record(["Unassigned Cards", &unassigned_summary(config)]);
     builder.push_record([
-        "Inactive Cards Lists",
-        &config.trello.inactive_cards_lists.join("\n"),
+        "Working Days",
+        &config
+            .calendar
+            .workdays
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    ]);
+    builder.push_record([
+        "Holidays",
+        &format!(
+            "{} configured{}",
+            config.calendar.holidays.len(),
+            config
+                .calendar
+                .holiday_calendar
+                .as_ref()
+                .map(|path| format!(", calendar {}", path.display()))
+                .unwrap_or_default()
+        ),
+    ]);
+    builder.push_record([
+        "Schedules",
+        &config
+            .schedule
+            .jobs
+            .iter()
+            .map(|job| {
+                format!(
+                    "{}: {} ({})",
+                    job.action, job.cron, config.schedule.timezone
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
     ]);
+    builder.push_record(["Board Overrides", &board_overrides_summary(config)]);
     let mut table = builder.build();
     table.with(Style::modern());
 
@@ -60,6 +112,128 @@ pub fn print_summary(config: &AppConfig) {
     );
 }
 
+fn join(values: &[impl ToString], separator: &str) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// List settings and channels of each board with overrides
+fn board_overrides_summary(config: &AppConfig) -> String {
+    config
+        .boards
+        .iter()
+        .map(|board| {
+            format!(
+                "{}: review lists [{}], inactive cards lists [{}], thresholds [{}], channels [{}]",
+                board.id,
+                join(config.review_lists(&board.id), ", "),
+                join(config.inactive_cards_lists(&board.id), ", "),
+                board
+                    .inactive_list_thresholds
+                    .iter()
+                    .map(|list_threshold| {
+                        format!("{}: {}", list_threshold.list, list_threshold.threshold)
+                    })
+                    .chain(
+                        board
+                            .inactive_threshold
+                            .map(|threshold| threshold.to_string())
+                    )
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                board
+                    .channels
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Manually mapped Trello users, and whether the other members are mapped automatically
+fn users_summary(config: &AppConfig) -> String {
+    let mut users: Vec<String> = config
+        .user_mapping
+        .iter()
+        .map(|m| m.trello_user.0.clone())
+        .collect();
+    if config.auto_user_mapping {
+        users.push("(others mapped automatically)".to_string());
+    }
+    users.join("\n")
+}
+
+/// Window and lists of the due dates action
+fn due_dates_summary(config: &AppConfig) -> String {
+    let lists = if config.due_dates.lists.is_empty() {
+        "all".to_string()
+    } else {
+        join(&config.due_dates.lists, ", ")
+    };
+    format!("window {}, lists [{lists}]", config.due_dates.window)
+}
+
+/// The configured label rules, one per line
+fn labels_summary(config: &AppConfig) -> String {
+    let labels = &config.labels;
+    [
+        ("include", &labels.include),
+        ("exclude", &labels.exclude),
+        ("urgent", &labels.urgent),
+        ("blocked", &labels.blocked),
+    ]
+    .into_iter()
+    .filter(|(_, names)| !names.is_empty())
+    .map(|(rule, names)| format!("{rule}: {}", names.join(", ")))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// List and member limits, and the channel of the alerts
+fn wip_limits_summary(config: &AppConfig) -> String {
+    let wip_limits = &config.wip_limits;
+    let limits = |list_limits: &[ListLimit]| {
+        list_limits
+            .iter()
+            .map(|list_limit| format!("{}: {}", list_limit.list, list_limit.limit))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "lists [{}], per member [{}], channel {}",
+        limits(&wip_limits.lists),
+        limits(&wip_limits.members),
+        wip_limits.channel.as_deref().unwrap_or("none")
+    )
+}
+
+/// List owners and the fallback owner of cards without members, and the channel of the action
+fn unassigned_summary(config: &AppConfig) -> String {
+    let unassigned = &config.unassigned;
+    let owners = unassigned
+        .owners
+        .iter()
+        .map(|list_owner| format!("{}: {}", list_owner.list, list_owner.owner))
+        .chain(
+            unassigned
+                .fallback
+                .iter()
+                .map(|fallback| format!("others: {fallback}")),
+        )
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "owners [{owners}], channel {}",
+        unassigned.channel.as_deref().unwrap_or("none")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
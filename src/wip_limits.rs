@@ -0,0 +1,382 @@
+use crate::{
+    ActionContext, BoardList, Recipient, TrelloUser,
+    calendar::WorkCalendar,
+    card_members,
+    config::{ActionConfig, LabelConfig, board_channel},
+    format_age,
+    i18n::Locale,
+    post_digest,
+    slack::{DeliveryReport, Message},
+    state::StateStore,
+    template::{MessageTemplate, Templates},
+    trello::{TrelloClient, moved_to_list_date},
+};
+use anyhow::{Result, bail};
+use log::info;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use time::{Duration, OffsetDateTime};
+
+/// Limits of a list, either of which may be unset
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListLimits {
+    pub list: Option<usize>,
+    /// Limit of the cards of each member within the list
+    pub member: Option<usize>,
+}
+
+/// Card counted towards the limits of its list
+#[derive(Clone, Debug)]
+pub struct WipCard {
+    card_name: String,
+    card_url: String,
+    members: Vec<TrelloUser>,
+    entered: OffsetDateTime,
+}
+
+/// List, or member within a list, with more cards than its limit
+#[derive(Clone, Debug)]
+pub struct WipViolation {
+    board_name: String,
+    list_name: String,
+    /// `None` if the limit of the whole list is exceeded
+    trello_user: Option<TrelloUser>,
+    count: usize,
+    limit: usize,
+    /// The cards exceeding the limit, which entered the list most recently, newest first
+    recent_cards: Vec<WipCard>,
+}
+
+/// ACTION: Post an alert for the lists exceeding their WIP limit to the channel of their board
+pub fn wip_limits(
+    context: &ActionContext,
+    state: &mut StateStore,
+    trello_to_slack_mapping: &HashMap<TrelloUser, Recipient>,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    target_lists: impl Iterator<Item = (BoardList, ListLimits)>,
+    channel: Option<&String>,
+) -> Result<()> {
+    let ActionContext {
+        trello_client,
+        slack_poster,
+        slack_directory: _,
+        calendar,
+        templates,
+        message_format: _,
+        snooze_buttons: _,
+        locale,
+        digest: _,
+        labels,
+        unassigned: _,
+        boards,
+    } = *context;
+
+    // no cards are fetched unless every board has a channel
+    let target_lists: Vec<_> = target_lists.collect();
+    let mut channels = HashMap::new();
+    for (board_list, _) in &target_lists {
+        let Some(channel) = board_channel(
+            boards,
+            &board_list.board_id,
+            |channels| &channels.wip_limits,
+            channel,
+        ) else {
+            bail!(
+                "No WIP limits channel configured for board {}, set WIP_LIMITS_CHANNEL or the channel of the board",
+                board_list.board_name
+            );
+        };
+        channels.insert(board_list.board_id.clone(), channel);
+    }
+
+    let mut violations = BTreeMap::<&String, Vec<WipViolation>>::new();
+    for (board_list, limits) in target_lists {
+        let cards = get_wip_cards(
+            trello_client,
+            labels,
+            trello_member_id_to_username,
+            &board_list,
+        )?;
+        let list_violations = find_violations(&board_list, limits, cards);
+        if !list_violations.is_empty() {
+            violations
+                .entry(channels[&board_list.board_id])
+                .or_default()
+                .extend(list_violations);
+        }
+    }
+
+    if violations.is_empty() {
+        info!("All lists are within their WIP limits, skipping alert");
+        return Ok(());
+    }
+
+    let action = ActionConfig::WipLimits.to_string();
+    let mut report = DeliveryReport::default();
+    let mut alerts = Vec::new();
+    for (channel, violations) in violations {
+        let message = compose_wip_limits_message(
+            templates,
+            calendar,
+            locale,
+            violations,
+            trello_to_slack_mapping,
+        )?;
+        alerts.push((channel, message));
+    }
+    post_digest(slack_poster, state, &mut report, &action, alerts)?;
+
+    report.finish()
+}
+
+/// Cards of the list that are counted towards its limits, with their mapped members
+fn get_wip_cards(
+    trello_client: &TrelloClient,
+    labels: &LabelConfig,
+    trello_member_id_to_username: &HashMap<String, TrelloUser>,
+    BoardList { list, .. }: &BoardList,
+) -> Result<Vec<WipCard>> {
+    info!("Processing list '{}' (ID: {})", list.name, list.id);
+
+    let cards = trello_client.get_cards(&list.id)?;
+
+    cards
+        .iter()
+        .filter(|card| labels.includes(card))
+        .map(|card| {
+            Ok(WipCard {
+                card_name: card.name.clone(),
+                card_url: card.url.clone(),
+                members: card_members(card, trello_member_id_to_username),
+                entered: moved_to_list_date(card)?,
+            })
+        })
+        .collect()
+}
+
+/// Violations of the list limit and of the member limit, members are ordered by name
+fn find_violations(
+    board_list: &BoardList,
+    limits: ListLimits,
+    mut cards: Vec<WipCard>,
+) -> Vec<WipViolation> {
+    cards.sort_by_key(|card| std::cmp::Reverse(card.entered)); // newest first
+
+    let violation = |trello_user: Option<TrelloUser>, cards: &[&WipCard], limit: usize| {
+        (cards.len() > limit).then(|| WipViolation {
+            board_name: board_list.board_name.clone(),
+            list_name: board_list.list.name.clone(),
+            trello_user,
+            count: cards.len(),
+            limit,
+            recent_cards: cards[..cards.len() - limit]
+                .iter()
+                .map(|card| (*card).clone())
+                .collect(),
+        })
+    };
+
+    let mut violations = Vec::new();
+    if let Some(limit) = limits.list {
+        let cards: Vec<&WipCard> = cards.iter().collect();
+        violations.extend(violation(None, &cards, limit));
+    }
+
+    if let Some(limit) = limits.member {
+        let mut cards_by_member = BTreeMap::<&str, Vec<&WipCard>>::new();
+        for card in &cards {
+            for trello_user in &card.members {
+                cards_by_member
+                    .entry(&trello_user.0)
+                    .or_default()
+                    .push(card);
+            }
+        }
+        violations.extend(
+            cards_by_member
+                .into_iter()
+                .filter_map(|(trello_user, cards)| {
+                    violation(Some(TrelloUser(trello_user.to_string())), &cards, limit)
+                }),
+        );
+    }
+
+    violations
+}
+
+#[derive(Debug, Serialize)]
+struct WipLimitsContext {
+    count: usize,
+    violations: Vec<WipViolationContext>,
+}
+
+#[derive(Debug, Serialize)]
+struct WipViolationContext {
+    board_name: String,
+    list_name: String,
+    trello_user: Option<String>,
+    /// Only set for mapped Trello users
+    slack_user: Option<String>,
+    count: usize,
+    limit: usize,
+    recent_cards: Vec<WipCardContext>,
+}
+
+#[derive(Debug, Serialize)]
+struct WipCardContext {
+    card_name: String,
+    card_url: String,
+    /// Formatted time since the card entered the list
+    age: String,
+}
+
+pub fn compose_wip_limits_message(
+    templates: &Templates,
+    calendar: &WorkCalendar,
+    locale: Locale,
+    violations: Vec<WipViolation>,
+    recipients: &HashMap<TrelloUser, Recipient>,
+) -> Result<Message> {
+    let translator = templates.translator(locale);
+    let violations: Vec<WipViolationContext> = violations
+        .into_iter()
+        .map(|violation| WipViolationContext {
+            slack_user: violation
+                .trello_user
+                .as_ref()
+                .and_then(|trello_user| recipients.get(trello_user))
+                .map(|recipient| recipient.slack_user.0.clone()),
+            trello_user: violation.trello_user.map(|trello_user| trello_user.0),
+            board_name: violation.board_name,
+            list_name: violation.list_name,
+            count: violation.count,
+            limit: violation.limit,
+            recent_cards: violation
+                .recent_cards
+                .into_iter()
+                .map(|card| WipCardContext {
+                    age: format_age(
+                        translator,
+                        calendar,
+                        calendar.working_time_since(card.entered),
+                    ),
+                    card_name: card.card_name,
+                    card_url: card.card_url,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let text = templates.render(
+        MessageTemplate::WipLimits,
+        locale,
+        WipLimitsContext {
+            count: violations.len(),
+            violations,
+        },
+    )?;
+
+    Ok(Message { text, blocks: None })
+}
+
+/// A list and a member exceeding their limits, e.g. to render the template
+pub fn sample_wip_violations(trello_user: &TrelloUser) -> Vec<WipViolation> {
+    let now = OffsetDateTime::now_utc();
+    let recent_cards: Vec<WipCard> = [1, 3]
+        .into_iter()
+        .map(|days| WipCard {
+            card_name: format!("Sample card {days}"),
+            card_url: format!("https://trello.com/c/sample{days}"),
+            members: vec![trello_user.clone()],
+            entered: now - Duration::days(days),
+        })
+        .collect();
+
+    vec![
+        WipViolation {
+            board_name: "Sample board".to_string(),
+            list_name: "In Progress".to_string(),
+            trello_user: None,
+            count: 7,
+            limit: 5,
+            recent_cards: recent_cards.clone(),
+        },
+        WipViolation {
+            board_name: "Sample board".to_string(),
+            list_name: "In Progress".to_string(),
+            trello_user: Some(trello_user.clone()),
+            count: 3,
+            limit: 2,
+            recent_cards: recent_cards[..1].to_vec(),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::List;
+
+    fn wip_card(name: &str, members: &[&str], days_in_list: i64) -> WipCard {
+        WipCard {
+            card_name: name.to_string(),
+            card_url: format!("https://trello.com/c/{name}"),
+            members: members
+                .iter()
+                .map(|member| TrelloUser((*member).to_string()))
+                .collect(),
+            entered: OffsetDateTime::now_utc() - Duration::days(days_in_list),
+        }
+    }
+
+    #[test]
+    fn it_names_the_most_recent_cards_exceeding_the_limits() {
+        let board_list = BoardList {
+            board_id: "board1".to_string(),
+            board_name: "Team".to_string(),
+            list: List {
+                id: "doing".to_string(),
+                name: "Doing".to_string(),
+            },
+        };
+        let cards = vec![
+            wip_card("old", &["alice"], 9),
+            wip_card("newest", &["bob"], 1),
+            wip_card("newer", &["alice", "bob"], 2),
+            wip_card("new", &["alice"], 3),
+        ];
+        let limits = ListLimits {
+            list: Some(2),
+            member: Some(2),
+        };
+
+        let violations = find_violations(&board_list, limits, cards);
+        let summary: Vec<(Option<&str>, usize, Vec<&str>)> = violations
+            .iter()
+            .map(|violation| {
+                (
+                    violation.trello_user.as_ref().map(|user| user.0.as_str()),
+                    violation.count,
+                    violation
+                        .recent_cards
+                        .iter()
+                        .map(|card| card.card_name.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            [
+                (None, 4, vec!["newest", "newer"]),
+                (Some("alice"), 3, vec!["newer"]),
+            ],
+            "bob is within the member limit"
+        );
+        assert!(
+            find_violations(&board_list, ListLimits::default(), Vec::new()).is_empty(),
+            "lists without limits are never exceeded"
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Child, Command, Output, Stdio},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+use time::{Duration, OffsetDateTime, format_description::well_known::Rfc3339};
+use tiny_http::{Header, Response, Server};
+
+/// In-process stand-in for the Trello and Slack APIs.
+///
+/// Trello requests are answered from the registered fixtures by path,
+/// Slack API calls and replies to `/responses/...` URLs are captured.
+pub struct MockServer {
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+    /// Slack API method and payload of each call
+    slack_requests: Arc<Mutex<Vec<(String, Value)>>>,
+    responses: Arc<Mutex<Vec<Value>>>,
+}
+
+#[derive(Default, Clone)]
+pub struct Fixtures {
+    /// Trello responses by path, e.g. `/1/boards/board1/members`
+    pub trello: HashMap<String, Value>,
+    /// Slack channels or users for which API calls fail with the given error
+    pub slack_errors: HashMap<String, String>,
+    /// Members of the Slack workspace returned by `users.list`
+    pub slack_users: Vec<Value>,
+}
+
+impl MockServer {
+    pub fn start(fixtures: Fixtures) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("mock server should start"));
+        let slack_requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(Vec::new()));
+
+        let handle = {
+            let server = server.clone();
+            let slack_requests = slack_requests.clone();
+            let responses = responses.clone();
+            std::thread::spawn(move || {
+                for mut request in server.incoming_requests() {
+                    let path = request
+                        .url()
+                        .split('?')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+
+                    let mut body = String::new();
+                    request
+                        .as_reader()
+                        .read_to_string(&mut body)
+                        .expect("request body should be readable");
+
+                    let (status, response) = if let Some(method) = path.strip_prefix("/api/") {
+                        // read methods are called with a form instead of JSON
+                        let payload: Value = serde_json::from_str(&body).unwrap_or_else(|_| {
+                            form_urlencoded::parse(body.as_bytes())
+                                .map(|(key, value)| (key.into_owned(), Value::from(value)))
+                                .collect::<serde_json::Map<_, _>>()
+                                .into()
+                        });
+                        let mut slack_requests =
+                            slack_requests.lock().expect("lock should not be poisoned");
+                        slack_requests.push((method.to_string(), payload.clone()));
+
+                        (
+                            200,
+                            slack_response(&fixtures, method, &payload, slack_requests.len()),
+                        )
+                    } else if path.starts_with("/responses/") {
+                        let payload: Value =
+                            serde_json::from_str(&body).expect("Slack reply should be JSON");
+                        responses
+                            .lock()
+                            .expect("lock should not be poisoned")
+                            .push(payload);
+                        (200, serde_json::json!({ "ok": true }))
+                    } else {
+                        match fixtures.trello.get(&path) {
+                            Some(fixture) => (200, fixture.clone()),
+                            None => (404, serde_json::json!({ "message": "not found" })),
+                        }
+                    };
+
+                    let response = Response::from_string(response.to_string())
+                        .with_status_code(status)
+                        .with_header(
+                            Header::from_bytes("Content-Type", "application/json")
+                                .expect("header should be valid"),
+                        );
+                    request.respond(response).expect("response should be sent");
+                }
+            })
+        };
+
+        MockServer {
+            server,
+            handle: Some(handle),
+            slack_requests,
+            responses,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!(
+            "http://{}",
+            self.server
+                .server_addr()
+                .to_ip()
+                .expect("mock server should listen on IP")
+        )
+    }
+
+    /// Captured `chat.postMessage` payloads by channel
+    pub fn slack_messages(&self) -> HashMap<String, Vec<Value>> {
+        let mut messages = HashMap::<String, Vec<Value>>::new();
+        for payload in self.slack_requests("chat.postMessage") {
+            messages
+                .entry(payload["channel"].as_str().unwrap_or_default().to_string())
+                .or_default()
+                .push(payload);
+        }
+        messages
+    }
+
+    /// Captured payloads of a Slack API method, e.g. `chat.update`
+    pub fn slack_requests(&self, method: &str) -> Vec<Value> {
+        self.slack_requests
+            .lock()
+            .expect("lock should not be poisoned")
+            .iter()
+            .filter(|(called_method, _)| called_method == method)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    /// Captured replies to response URLs, waiting up to five seconds for the expected number
+    pub fn responses(&self, count: usize) -> Vec<Value> {
+        for _ in 0..50 {
+            let responses = self.responses.lock().expect("lock should not be poisoned");
+            if responses.len() >= count {
+                return responses.clone();
+            }
+            drop(responses);
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        self.responses
+            .lock()
+            .expect("lock should not be poisoned")
+            .clone()
+    }
+}
+
+/// Answers a Slack API call, posted messages get a unique timestamp
+fn slack_response(fixtures: &Fixtures, method: &str, payload: &Value, call: usize) -> Value {
+    let channel = payload["channel"].as_str().unwrap_or_default();
+    let user = payload["user"].as_str().unwrap_or_default();
+    if let Some(error) = fixtures
+        .slack_errors
+        .get(channel)
+        .or_else(|| fixtures.slack_errors.get(user))
+    {
+        return serde_json::json!({ "ok": false, "error": error });
+    }
+
+    match method {
+        "chat.postMessage" => {
+            serde_json::json!({ "ok": true, "channel": channel, "ts": format!("{call}.0") })
+        }
+        "chat.update" => {
+            serde_json::json!({ "ok": true, "channel": channel, "ts": payload["ts"] })
+        }
+        "users.list" => serde_json::json!({
+            "ok": true,
+            "members": fixtures.slack_users,
+            "response_metadata": { "next_cursor": "" },
+        }),
+        _ => serde_json::json!({ "ok": true }),
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("mock server should shut down");
+        }
+    }
+}
+
+/// A fresh working directory for a run of the binary, since debug builds write responses to `debug/`
+pub fn working_directory(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("trello-to-slack-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(directory.join("debug")).expect("working directory should be created");
+    directory
+}
+
+/// Runs the binary against the mock server with a clean environment
+pub fn run(
+    server: &MockServer,
+    working_directory: &Path,
+    env: &[(&str, &str)],
+    args: &[&str],
+) -> Output {
+    command(server, working_directory, env, args)
+        .output()
+        .expect("binary should run")
+}
+
+/// Starts the binary in the background, e.g. for the `serve` command
+pub fn spawn(
+    server: &MockServer,
+    working_directory: &Path,
+    env: &[(&str, &str)],
+    args: &[&str],
+) -> Child {
+    command(server, working_directory, env, args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("binary should start")
+}
+
+fn command(
+    server: &MockServer,
+    working_directory: &Path,
+    env: &[(&str, &str)],
+    args: &[&str],
+) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_trello-to-slack"));
+    command
+        .current_dir(working_directory)
+        .env_clear()
+        .env("TRELLO_API_URL", format!("{}/1", server.url()))
+        .env("SLACK_API_URL", format!("{}/api", server.url()))
+        .env("SLACK_BOT_TOKEN", "xoxb-test")
+        .env("TRELLO_KEY", "key")
+        .env("TRELLO_TOKEN", "token")
+        .env("WORKDAYS", "mon,tue,wed,thu,fri,sat,sun")
+        .env("RETRY_MAX_ATTEMPTS", "1")
+        .envs(env.iter().copied())
+        .args(args);
+    command
+}
+
+/// Runs the `serve` command in the background with the interactivity endpoint on a free port.
+///
+/// Returns the daemon and the address of the endpoint, once it accepts connections.
+pub fn serve(
+    server: &MockServer,
+    working_directory: &Path,
+    env: &[(&str, &str)],
+) -> (Child, String) {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("should find a free port")
+        .port();
+    let address = format!("127.0.0.1:{port}");
+
+    let mut env = env.to_vec();
+    env.extend([
+        ("SLACK_SIGNING_SECRET", SIGNING_SECRET),
+        ("INTERACTIVITY_ADDRESS", address.as_str()),
+        ("SCHEDULES", "pending-reviews=0 0 1 1 *"),
+    ]);
+    let daemon = spawn(server, working_directory, &env, &["serve"]);
+
+    for _ in 0..50 {
+        if std::net::TcpStream::connect(&address).is_ok() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    (daemon, address)
+}
+
+pub const SIGNING_SECRET: &str = "secret";
+
+/// Posts a form to the interactivity endpoint, signed like Slack does, and returns the status code
+pub fn post_signed(address: &str, path: &str, form: &[(&str, &str)]) -> u16 {
+    let body = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(form)
+        .finish();
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp().to_string();
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(SIGNING_SECRET.as_bytes()).expect("key should be valid");
+    mac.update(format!("v0:{timestamp}:{body}").as_bytes());
+    let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+
+    reqwest::blocking::Client::new()
+        .post(format!("http://{address}{path}"))
+        .header("X-Slack-Request-Timestamp", timestamp)
+        .header("X-Slack-Signature", signature)
+        .body(body)
+        .send()
+        .expect("request should be sent")
+        .status()
+        .as_u16()
+}
+
+pub fn member(id: &str, username: &str) -> Value {
+    serde_json::json!({ "id": id, "username": username, "fullName": username.to_uppercase() })
+}
+
+pub fn slack_user(id: &str, real_name: &str) -> Value {
+    serde_json::json!({ "id": id, "real_name": real_name, "profile": { "real_name": real_name } })
+}
+
+pub fn board(id: &str, name: &str) -> Value {
+    serde_json::json!({ "id": id, "name": name, "shortLink": id })
+}
+
+pub fn list(id: &str, name: &str) -> Value {
+    serde_json::json!({ "id": id, "name": name })
+}
+
+/// A card without actions, so that its creation date (encoded in the ID) is used as the date it entered the list
+pub fn card(list_id: &str, name: &str, member_ids: &[&str], age: Duration) -> Value {
+    let created = OffsetDateTime::now_utc() - age;
+    let id = format!("{:08x}{:016x}", created.unix_timestamp(), name.len());
+
+    serde_json::json!({
+        "id": id,
+        "idList": list_id,
+        "idMembers": member_ids,
+        "name": name,
+        "dateLastActivity": created.format(&Rfc3339).expect("date should be formattable"),
+        "url": format!("https://trello.com/c/{id}"),
+        "actions": [],
+    })
+}
+
+/// A card with a due date relative to now, which is overdue for a negative `due_in`
+pub fn due_card(
+    list_id: &str,
+    name: &str,
+    member_ids: &[&str],
+    due_in: Duration,
+    due_complete: bool,
+) -> Value {
+    let mut card = card(list_id, name, member_ids, Duration::days(1));
+    let due = OffsetDateTime::now_utc() + due_in;
+    card["due"] = due
+        .format(&Rfc3339)
+        .expect("date should be formattable")
+        .into();
+    card["dueComplete"] = due_complete.into();
+    card
+}
+
+/// Adds labels with the given names to a card
+pub fn with_labels(mut card: Value, names: &[&str]) -> Value {
+    card["labels"] = names
+        .iter()
+        .map(|name| serde_json::json!({ "id": name, "name": name, "color": "green" }))
+        .collect();
+    card
+}
+
+/// A `createCard` action of the card in `list`, or an `updateCard` action moving it from `list_before`
+pub fn list_action(
+    card: &Value,
+    list_before: Option<(&str, &str)>,
+    list: (&str, &str),
+    ago: Duration,
+) -> Value {
+    let date = OffsetDateTime::now_utc() - ago;
+    let list_json = |(id, name): (&str, &str)| serde_json::json!({ "id": id, "name": name });
+    let (r#type, created_in, list_after) = match list_before {
+        Some(_) => ("updateCard", None, Some(list_json(list))),
+        None => ("createCard", Some(list_json(list)), None),
+    };
+
+    serde_json::json!({
+        "id": format!("{:08x}{:016x}", date.unix_timestamp(), ago.whole_seconds()),
+        "idMemberCreator": "m1",
+        "date": date.format(&Rfc3339).expect("date should be formattable"),
+        "type": r#type,
+        "data": {
+            "board": { "id": "board1", "name": "Team Board", "shortLink": "board1" },
+            "card": { "id": card["id"], "idShort": 1, "name": card["name"], "shortLink": "card" },
+            "list": created_in,
+            "listBefore": list_before.map(list_json),
+            "listAfter": list_after,
+        },
+        "memberCreator": {
+            "id": "m1",
+            "username": "alice",
+            "fullName": "ALICE",
+            "initials": "A",
+            "activityBlocked": false,
+            "nonPublicAvailable": false,
+        },
+    })
+}
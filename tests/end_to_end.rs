@@ -0,0 +1,1045 @@
+mod common;
+
+use common::{
+    Fixtures, MockServer, board, card, due_card, list, list_action, member, post_signed, run,
+    serve, slack_user, spawn, with_labels, working_directory,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use time::Duration;
+
+fn board_fixtures() -> Fixtures {
+    let mut fixtures = Fixtures::default();
+
+    fixtures.trello.insert(
+        "/1/boards/board1".to_string(),
+        board("board1", "Team Board"),
+    );
+    fixtures.trello.insert(
+        "/1/boards/board1/members".to_string(),
+        serde_json::json!([
+            member("m1", "alice"),
+            member("m2", "bob"),
+            member("m3", "carol"),
+        ]),
+    );
+    fixtures.trello.insert(
+        "/1/boards/board1/lists".to_string(),
+        serde_json::json!([
+            list("review", "Review"),
+            list("sprint", "Sprint"),
+            list("done", "Done"),
+        ]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/review/cards".to_string(),
+        serde_json::json!([
+            card("review", "Review A", &["m1"], Duration::days(3)),
+            card("review", "Review B", &["m1", "m2"], Duration::hours(2)),
+            card("review", "Review C", &["m3"], Duration::days(1)),
+            card("review", "Review D", &[], Duration::days(1)),
+        ]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/sprint/cards".to_string(),
+        serde_json::json!([
+            card("sprint", "Sprint E", &["m2"], Duration::weeks(5)),
+            card("sprint", "Sprint F", &["m1"], Duration::days(1)),
+        ]),
+    );
+
+    fixtures
+}
+
+const ENV: &[(&str, &str)] = &[
+    ("TRELLO_BOARD_IDS", "board1"),
+    ("TRELLO_REVIEW_LISTS", "Review"),
+    ("TRELLO_INACTIVE_CARDS_LISTS", "Sprint"),
+    ("USER_MAPPING", "alice=U_ALICE,bob=U_BOB"),
+];
+
+fn message_text(messages: &[serde_json::Value]) -> &str {
+    assert_eq!(messages.len(), 1, "expected exactly one message");
+    messages[0]["markdown_text"]
+        .as_str()
+        .expect("message should have markdown text")
+}
+
+#[test]
+fn it_sends_pending_reviews_to_each_member() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("pending-reviews");
+
+    let output = run(&server, &directory, ENV, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 2, "only mapped users are notified");
+
+    let alice = message_text(&messages["U_ALICE"]);
+    assert!(alice.contains("Du hast 2 ausstehende Reviews"));
+    assert!(alice.contains("[Review A]"));
+    assert!(alice.contains("[Review B]"));
+    assert!(
+        alice.find("[Review A]") < alice.find("[Review B]"),
+        "oldest review comes first"
+    );
+
+    let bob = message_text(&messages["U_BOB"]);
+    assert!(bob.contains("Du hast 1 ausstehendes Review:"));
+    assert!(bob.contains("[Review B]"));
+    assert!(!bob.contains("[Review A]"));
+}
+
+#[test]
+fn it_sends_one_reminder_per_slack_user_of_several_trello_users() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("shared-slack-user");
+
+    let mut env = ENV.to_vec();
+    env.retain(|(name, _)| *name != "USER_MAPPING");
+    env.push(("USER_MAPPING", "alice=U_ALICE,bob=U_ALICE"));
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 1, "both Trello users share a Slack user");
+
+    let text = message_text(&messages["U_ALICE"]);
+    assert!(text.contains("Du hast 2 ausstehende Reviews"));
+    assert!(text.contains("[Review A]"));
+    assert_eq!(text.matches("[Review B]").count(), 1, "shared card once");
+}
+
+#[test]
+fn it_remembers_the_cards_of_all_trello_users_of_a_slack_user() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("shared-slack-user-state");
+
+    let mut env = ENV.to_vec();
+    env.retain(|(name, _)| *name != "USER_MAPPING");
+    env.push(("USER_MAPPING", "alice=U_ALICE,carol=U_ALICE"));
+    env.push(("STATE_FILE", "state.json"));
+    env.push(("ONLY_NEW_CARDS", "true"));
+
+    for _ in 0..2 {
+        let output = run(&server, &directory, &env, &["pending-reviews"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let messages = server.slack_messages();
+    let text = message_text(&messages["U_ALICE"]);
+    assert!(text.contains("[Review A]"));
+    assert!(text.contains("[Review C]"));
+}
+
+#[test]
+fn it_sends_inactive_cards_above_the_threshold() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("inactive-cards");
+
+    let output = run(&server, &directory, ENV, &["inactive-cards"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 1, "only Bob has an inactive card");
+
+    let bob = message_text(&messages["U_BOB"]);
+    assert!(bob.contains("[Sprint E]"));
+    assert!(bob.contains("In Liste seit 5 Wochen"));
+}
+
+#[test]
+fn it_reminds_of_due_dates_grouped_by_urgency() {
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/sprint/cards".to_string(),
+        serde_json::json!([
+            due_card("sprint", "Due Soon", &["m1"], Duration::days(3), false),
+            due_card("sprint", "Overdue", &["m1"], -Duration::days(2), false),
+            due_card("sprint", "Due Later", &["m1"], Duration::weeks(3), false),
+            due_card("sprint", "Done Early", &["m2"], -Duration::days(1), true),
+        ]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/done/cards".to_string(),
+        serde_json::json!([card("done", "No Due Date", &["m2"], Duration::weeks(5))]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("due-dates");
+
+    let mut env = ENV.to_vec();
+    env.push(("LOCALE", "en"));
+    let output = run(&server, &directory, &env, &["due-dates"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 1, "only Alice has incomplete due dates");
+
+    let alice = message_text(&messages["U_ALICE"]);
+    assert!(alice.contains("You have 2 cards with an upcoming due date"));
+    assert!(alice.contains("🚨 Overdue**\n- [Overdue]"));
+    assert!(alice.contains("Due this week**\n- [Due Soon]"));
+    assert!(!alice.contains("[Due Later]"), "outside of the window");
+}
+
+#[test]
+fn it_applies_label_rules() {
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/sprint/cards".to_string(),
+        serde_json::json!([
+            with_labels(
+                card("sprint", "On Hold", &["m2"], Duration::weeks(5)),
+                &["on-hold"]
+            ),
+            with_labels(
+                card("sprint", "Urgent", &["m1"], Duration::days(1)),
+                &["urgent"]
+            ),
+            with_labels(
+                card("sprint", "Blocked", &["m2"], Duration::days(2)),
+                &["blocked"]
+            ),
+        ]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("labels");
+
+    let mut env = ENV.to_vec();
+    env.extend([
+        ("EXCLUDE_LABELS", "on-hold"),
+        ("URGENT_LABELS", "urgent"),
+        ("BLOCKED_LABELS", "blocked"),
+    ]);
+    for action in ["inactive-cards", "blocked-cards"] {
+        let output = run(&server, &directory, &env, &[action]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let messages = server.slack_messages();
+    assert_eq!(messages["U_ALICE"].len(), 1);
+    let alice = message_text(&messages["U_ALICE"]);
+    assert!(
+        alice.contains("[Urgent](https://trello.com/c/") && alice.contains("❗"),
+        "urgent cards are reported below the threshold"
+    );
+
+    let bob = message_text(&messages["U_BOB"]);
+    assert!(bob.contains("blockiert"));
+    assert!(bob.contains("[Blocked]"));
+    assert!(!bob.contains("[On Hold]"));
+}
+
+#[test]
+fn it_alerts_the_channel_of_exceeded_wip_limits() {
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/sprint/cards".to_string(),
+        serde_json::json!([
+            card("sprint", "Old", &["m1"], Duration::days(5)),
+            card("sprint", "Newer", &["m1"], Duration::days(3)),
+            card("sprint", "Newest", &["m2"], Duration::days(1)),
+        ]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("wip-limits");
+
+    let mut env = ENV.to_vec();
+    env.extend([
+        ("LOCALE", "en"),
+        ("WIP_LIMITS", "Sprint=2,Review=5"),
+        ("WIP_MEMBER_LIMITS", "Sprint=1"),
+        ("WIP_LIMITS_CHANNEL", "C_TEAM"),
+    ]);
+    let output = run(&server, &directory, &env, &["wip-limits"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 1, "only the channel is alerted");
+
+    let alert = message_text(&messages["C_TEAM"]);
+    assert!(alert.contains("2 WIP limits are exceeded"), "{alert}");
+    assert!(alert.contains("**Team Board › Sprint**: 3 cards with a limit of 2"));
+    assert!(alert.contains("**Team Board › Sprint** (<@U_ALICE>): 2 cards with a limit of 1"));
+    assert!(alert.contains("- [Newest]"));
+    assert!(alert.contains("- [Newer]"));
+    assert!(
+        !alert.contains("[Old]"),
+        "only the most recent cards are named"
+    );
+    assert!(
+        !alert.contains("Review"),
+        "the review list is within its limit"
+    );
+
+    env.retain(|(name, _)| *name != "WIP_LIMITS_CHANNEL");
+    let output = run(&server, &directory, &env, &["wip-limits"]);
+    assert!(!output.status.success(), "the alert has no channel");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No WIP limits channel configured"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn it_routes_unassigned_cards_to_the_list_owner() {
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/review/cards".to_string(),
+        serde_json::json!([
+            card("review", "Review A", &["m1"], Duration::days(3)),
+            with_labels(
+                card("review", "Review D", &[], Duration::days(1)),
+                &["blocked"]
+            ),
+        ]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/sprint/cards".to_string(),
+        serde_json::json!([
+            card("sprint", "Sprint E", &["m2"], Duration::weeks(5)),
+            card("sprint", "Unassigned", &[], Duration::weeks(3)),
+            card("sprint", "Former member", &["m9"], Duration::weeks(4)),
+        ]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("unassigned-cards");
+
+    let mut env = ENV.to_vec();
+    env.extend([
+        ("LIST_OWNERS", "Review=bob"),
+        ("BLOCKED_LABELS", "blocked"),
+        ("UNASSIGNED_CARDS_CHANNEL", "C_TEAM"),
+    ]);
+    for action in ["pending-reviews", "blocked-cards", "unassigned-cards"] {
+        let output = run(&server, &directory, &env, &[action]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let messages = server.slack_messages();
+    let bob: Vec<&str> = messages["U_BOB"]
+        .iter()
+        .map(|message| message["markdown_text"].as_str().unwrap_or_default())
+        .collect();
+    assert_eq!(bob.len(), 2, "{bob:?}");
+    assert!(
+        bob.iter().all(|text| text.contains("[Review D]")),
+        "the owner is reminded of the review and the blocked card: {bob:?}"
+    );
+    assert!(!message_text(&messages["U_ALICE"]).contains("[Review D]"));
+
+    let channel = message_text(&messages["C_TEAM"]);
+    assert!(
+        channel.contains("3 Karten haben keine Mitglieder"),
+        "{channel}"
+    );
+    assert!(channel.contains("_Team Board › Review_ (Verantwortlich: <@U_BOB>)\n- [Review D]"));
+    assert!(
+        channel.contains("_Team Board › Sprint_\n- [Former member]"),
+        "members who left the board do not count: {channel}"
+    );
+    assert!(channel.contains("- [Unassigned]"));
+    assert!(!channel.contains("[Sprint E]"));
+
+    env.retain(|(name, _)| *name != "UNASSIGNED_CARDS_CHANNEL");
+    let output = run(&server, &directory, &env, &["unassigned-cards"]);
+    assert!(!output.status.success(), "the cards have no channel");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("No unassigned cards channel configured"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn it_reports_cycle_and_lead_times_from_the_list_moves() {
+    let done = card("done", "Done Card", &["m1"], Duration::days(10));
+    let ongoing = card("review", "Ongoing", &["m2"], Duration::days(4));
+    let progress = ("progress", "In Progress");
+    let review = ("review", "Review");
+    let actions = serde_json::json!([
+        list_action(&done, Some(review), ("done", "Done"), Duration::days(1)),
+        list_action(&ongoing, Some(progress), review, Duration::days(2)),
+        list_action(&done, Some(progress), review, Duration::days(4)),
+        list_action(&ongoing, None, progress, Duration::days(4)),
+        list_action(
+            &done,
+            Some(("backlog", "Backlog")),
+            progress,
+            Duration::days(7)
+        ),
+    ]);
+
+    let mut fixtures = board_fixtures();
+    fixtures
+        .trello
+        .insert("/1/boards/board1/actions".to_string(), actions);
+    fixtures.trello.insert(
+        "/1/boards/board1/cards".to_string(),
+        serde_json::json!([done, ongoing]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("metrics");
+
+    let output = run(&server, &directory, ENV, &["metrics", "--since", "4w"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let csv = String::from_utf8_lossy(&output.stdout);
+    assert!(csv.starts_with("metric,dimension,group,count,mean_hours,p50_hours"));
+    assert!(
+        csv.contains("dwell_time,list,Team Board › In Progress,2,60,48,72,72\n"),
+        "{csv}"
+    );
+    assert!(csv.contains("cycle_time,member,alice,1,144,144,144,144\n"));
+    assert!(csv.contains("lead_time,board,Team Board,1,216,216,216,216\n"));
+    assert!(!csv.contains("bob"), "the ongoing card is not done");
+
+    let output = run(
+        &server,
+        &directory,
+        ENV,
+        &["metrics", "--format", "json", "--output", "metrics.json"],
+    );
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(directory.join("metrics.json")).expect("report should be written"),
+    )
+    .expect("report should be JSON");
+    let cards = report["cards"].as_array().expect("cards should be listed");
+    assert_eq!(cards.len(), 2);
+    assert!(cards.iter().any(|card| card["card_name"] == "Done Card"
+        && card["cycle_hours"] == 144.0
+        && card["timeline"].as_array().map(Vec::len) == Some(4)));
+}
+
+#[test]
+fn it_selects_lists_by_board_and_pattern() {
+    let mut fixtures = board_fixtures();
+    fixtures
+        .trello
+        .insert("/1/boards/board2".to_string(), board("board2", "Ops Board"));
+    fixtures.trello.insert(
+        "/1/boards/board2/members".to_string(),
+        serde_json::json!([member("m1", "alice"), member("m2", "bob")]),
+    );
+    fixtures.trello.insert(
+        "/1/boards/board2/lists".to_string(),
+        serde_json::json!([list("ops-review", "Review"), list("ops-sprint", "Sprint 2")]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/ops-review/cards".to_string(),
+        serde_json::json!([card("ops-review", "Ops Review", &["m1"], Duration::days(1))]),
+    );
+    fixtures.trello.insert(
+        "/1/lists/ops-sprint/cards".to_string(),
+        serde_json::json!([card(
+            "ops-sprint",
+            "Ops Sprint",
+            &["m2"],
+            Duration::weeks(5)
+        )]),
+    );
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("list-selectors");
+
+    let env = [
+        ("TRELLO_BOARD_IDS", "board1,board2"),
+        ("TRELLO_REVIEW_LISTS", "Team Board/Review"),
+        ("TRELLO_INACTIVE_CARDS_LISTS", "Sprint*"),
+        ("USER_MAPPING", "alice=U_ALICE,bob=U_BOB"),
+    ];
+
+    for action in ["pending-reviews", "inactive-cards"] {
+        let output = run(&server, &directory, &env, &[action]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let messages = server.slack_messages();
+    let alice = messages["U_ALICE"]
+        .iter()
+        .map(|message| message["markdown_text"].as_str().unwrap_or_default())
+        .collect::<String>();
+    assert!(alice.contains("[Review A]"), "{alice}");
+    assert!(
+        !alice.contains("[Ops Review]"),
+        "the review list of the other board is not selected"
+    );
+    let bob = messages["U_BOB"]
+        .iter()
+        .map(|message| message["markdown_text"].as_str().unwrap_or_default())
+        .collect::<String>();
+    assert!(bob.contains("[Sprint E]"), "{bob}");
+    assert!(bob.contains("[Ops Sprint]"), "{bob}");
+}
+
+#[test]
+fn it_uses_the_locale_of_each_user() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("locales");
+
+    let mut env = ENV.to_vec();
+    env.retain(|(name, _)| *name != "USER_MAPPING");
+    env.push(("USER_MAPPING", "alice=U_ALICE:de,bob=U_BOB"));
+    env.push(("LOCALE", "en"));
+
+    let output = run(&server, &directory, &env, &["inactive-cards"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    let bob = message_text(&messages["U_BOB"]);
+    assert!(bob.contains("The following card has been in the sprint"));
+    assert!(bob.contains("In list for 5 weeks"));
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(output.status.success());
+
+    let messages = server.slack_messages();
+    assert!(message_text(&messages["U_ALICE"]).contains("Du hast 2 ausstehende Reviews"));
+}
+
+#[test]
+fn it_sends_block_kit_messages_with_markdown_fallback() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("blocks");
+
+    let output = run(
+        &server,
+        &directory,
+        ENV,
+        &["--message-format=blocks", "inactive-cards"],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    let bob = &messages["U_BOB"][0];
+    assert!(bob.get("markdown_text").is_none());
+    assert!(
+        bob["text"]
+            .as_str()
+            .expect("fallback text should be sent")
+            .contains("In Liste seit 5 Wochen")
+    );
+
+    let blocks = bob["blocks"].as_array().expect("blocks should be sent");
+    assert_eq!(blocks[0]["type"], "header");
+    assert!(
+        blocks[2]["text"]["text"]
+            .as_str()
+            .expect("card section should have a text")
+            .contains("|Sprint E>")
+    );
+    assert_eq!(blocks[2]["accessory"]["type"], "button");
+    assert_eq!(blocks[3]["elements"][1]["text"], "📋 Team Board › Sprint");
+}
+
+#[test]
+fn it_skips_cards_snoozed_via_the_interactivity_endpoint() {
+    let fixtures = board_fixtures();
+    let review_b = fixtures.trello["/1/lists/review/cards"][1]["id"].clone();
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("snooze");
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("should find a free port")
+        .port();
+    let address = format!("127.0.0.1:{port}");
+
+    let mut env = ENV.to_vec();
+    env.extend([
+        ("STATE_FILE", "state.json"),
+        ("SLACK_SIGNING_SECRET", "secret"),
+        ("INTERACTIVITY_ADDRESS", address.as_str()),
+        ("SCHEDULES", "pending-reviews=0 0 1 1 *"),
+    ]);
+    let mut daemon = spawn(&server, &directory, &env, &["serve"]);
+
+    // Bob presses "Snooze 1 day" on his only review
+    let payload = serde_json::json!({
+        "type": "block_actions",
+        "user": { "id": "U_BOB" },
+        "actions": [{
+            "action_id": "snooze_day",
+            "value": serde_json::json!({ "card_id": review_b, "list_id": "review" }).to_string(),
+        }],
+    });
+    let body = format!(
+        "payload={}",
+        form_urlencoded::byte_serialize(payload.to_string().as_bytes()).collect::<String>()
+    );
+    let timestamp = time::OffsetDateTime::now_utc().unix_timestamp().to_string();
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").expect("key should be valid");
+    mac.update(format!("v0:{timestamp}:{body}").as_bytes());
+    let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+
+    let client = reqwest::blocking::Client::new();
+    let mut status = None;
+    for _ in 0..50 {
+        let response = client
+            .post(format!("http://{address}/slack/interactions"))
+            .header("X-Slack-Request-Timestamp", &timestamp)
+            .header("X-Slack-Signature", &signature)
+            .body(body.clone())
+            .send();
+        if let Ok(response) = response {
+            status = Some(response.status().as_u16());
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    let state_file = directory.join("state.json");
+    for _ in 0..50 {
+        let state = std::fs::read_to_string(&state_file).unwrap_or_default();
+        if state.contains(review_b.as_str().unwrap_or_default()) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    daemon.kill().expect("daemon should stop");
+    daemon.wait().expect("daemon should exit");
+    assert_eq!(status, Some(200));
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert!(messages.contains_key("U_ALICE"));
+    assert!(
+        !messages.contains_key("U_BOB"),
+        "Bob snoozed his only review"
+    );
+}
+
+#[test]
+fn it_answers_slash_commands_ephemerally() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("slash-commands");
+    let (mut daemon, address) = serve(&server, &directory, ENV);
+
+    let response_url = format!("{}/responses/1", server.url());
+    let status = post_signed(
+        &address,
+        "/slack/commands",
+        &[
+            ("command", "/trello-reviews"),
+            ("user_id", "U_BOB"),
+            ("response_url", &response_url),
+        ],
+    );
+    let unknown_user = post_signed(
+        &address,
+        "/slack/commands",
+        &[
+            ("command", "/trello-stale"),
+            ("user_id", "U_CAROL"),
+            ("response_url", &response_url),
+        ],
+    );
+
+    let responses = server.responses(2);
+    daemon.kill().expect("daemon should stop");
+    daemon.wait().expect("daemon should exit");
+    assert_eq!((status, unknown_user), (200, 200));
+    assert_eq!(responses.len(), 2);
+
+    let reviews = &responses[0];
+    assert_eq!(reviews["response_type"], "ephemeral");
+    let text = reviews["text"].as_str().expect("reply should have a text");
+    assert!(text.contains("Du hast 1 ausstehendes Review:"), "{text}");
+    assert!(text.contains("[Review B]"));
+
+    assert!(
+        responses[1]["text"]
+            .as_str()
+            .expect("reply should have a text")
+            .contains("kein Trello-Benutzer")
+    );
+    assert!(
+        server.slack_messages().is_empty(),
+        "nothing is posted to the channels"
+    );
+}
+
+#[test]
+fn it_posts_a_digest_to_the_team_channel() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("digest");
+
+    let mut env = ENV.to_vec();
+    env.push(("PENDING_REVIEWS_DIGEST_CHANNEL", "C_TEAM"));
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let messages = server.slack_messages();
+    assert_eq!(messages.len(), 3, "the users are notified as well");
+
+    let digest = message_text(&messages["C_TEAM"]);
+    assert!(digest.contains("Im Team warten 3 Reviews"), "{digest}");
+    assert!(digest.contains("**<@U_ALICE>** (2 Karten)"));
+    assert!(digest.contains("**<@U_BOB>** (1 Karte)"));
+    assert!(
+        digest.contains("**carol** (1 Karte)"),
+        "unmapped users are listed by their Trello name"
+    );
+    assert!(digest.contains("_Team Board › Review_"));
+    assert!(digest.contains("Am längsten wartet: [Review A]"));
+}
+
+#[test]
+fn it_updates_the_previous_reminder_instead_of_posting_a_new_one() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("update-policy");
+
+    let mut env = ENV.to_vec();
+    env.push(("STATE_FILE", "state.json"));
+    env.push(("MESSAGE_UPDATE_POLICY", "update"));
+
+    for _ in 0..2 {
+        let output = run(&server, &directory, &env, &["pending-reviews"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let messages = server.slack_messages();
+    assert_eq!(messages["U_ALICE"].len(), 1, "only the first run posts");
+    let updates = server.slack_requests("chat.update");
+    assert_eq!(updates.len(), 2);
+    let bob_update = updates
+        .iter()
+        .find(|update| update["channel"] == "U_BOB")
+        .expect("Bob's message should be updated");
+
+    // Bob's review is done, his reminder is replaced by an all-clear
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/review/cards".to_string(),
+        serde_json::json!([card("review", "Review A", &["m1"], Duration::days(3))]),
+    );
+    let server = MockServer::start(fixtures);
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(server.slack_messages().is_empty());
+    let updates = server.slack_requests("chat.update");
+    let retired = updates
+        .iter()
+        .find(|update| update["channel"] == "U_BOB")
+        .expect("Bob's message should be retired");
+    assert_eq!(retired["ts"], bob_update["ts"]);
+    assert!(
+        retired["blocks"][0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("keine ausstehenden Reviews")),
+        "{retired}"
+    );
+}
+
+#[test]
+fn it_replaces_the_blocks_of_a_retired_reminder() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("retire-blocks");
+
+    let mut env = ENV.to_vec();
+    env.extend([
+        ("STATE_FILE", "state.json"),
+        ("MESSAGE_UPDATE_POLICY", "update"),
+        ("MESSAGE_FORMAT", "blocks"),
+    ]);
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server.slack_messages()["U_BOB"][0]["blocks"].is_array());
+
+    // Bob's review is done, his reminder is replaced by an all-clear
+    let mut fixtures = board_fixtures();
+    fixtures.trello.insert(
+        "/1/lists/review/cards".to_string(),
+        serde_json::json!([card("review", "Review A", &["m1"], Duration::days(3))]),
+    );
+    let server = MockServer::start(fixtures);
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let updates = server.slack_requests("chat.update");
+    let retired = updates
+        .iter()
+        .find(|update| update["channel"] == "U_BOB")
+        .expect("Bob's message should be retired");
+    assert!(
+        retired.get("markdown_text").is_none(),
+        "markdown_text cannot be combined with blocks"
+    );
+    let blocks = retired["blocks"]
+        .as_array()
+        .expect("the blocks of the reminder should be replaced");
+    assert_eq!(blocks.len(), 1, "{retired}");
+    assert_eq!(blocks[0]["type"], "markdown");
+    assert!(
+        retired["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("keine ausstehenden Reviews")),
+        "{retired}"
+    );
+}
+
+#[test]
+fn it_maps_the_remaining_members_automatically() {
+    let mut fixtures = board_fixtures();
+    fixtures.slack_users = vec![
+        slack_user("U_ALICE", "Alice"),
+        slack_user("U_BOB", "Bob"),
+        slack_user("U_CAROL1", "Carol"),
+        slack_user("U_CAROL2", "Carol"),
+    ];
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("auto-user-mapping");
+
+    let env = [
+        ("TRELLO_BOARD_IDS", "board1"),
+        ("TRELLO_REVIEW_LISTS", "Review"),
+        ("USER_MAPPING", "alice=U_MANUAL"),
+        ("AUTO_USER_MAPPING", "true"),
+    ];
+
+    let output = run(&server, &directory, &env, &["mapping"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("U_MANUAL"), "{stdout}");
+    assert!(stdout.contains("manual"), "{stdout}");
+    assert!(stdout.contains("U_BOB"), "{stdout}");
+    assert!(
+        stdout.contains("- carol (CAROL), ambiguous name: U_CAROL1, U_CAROL2"),
+        "{stdout}"
+    );
+    assert!(server.slack_messages().is_empty());
+
+    let output = run(&server, &directory, &env, &["pending-reviews"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let messages = server.slack_messages();
+    assert!(
+        messages.contains_key("U_MANUAL"),
+        "manual mappings take precedence"
+    );
+    assert!(messages.contains_key("U_BOB"));
+    assert!(!messages.contains_key("U_ALICE"));
+    assert_eq!(messages.len(), 2);
+}
+
+#[test]
+fn it_checks_the_configuration_against_the_boards() {
+    let mut fixtures = board_fixtures();
+    fixtures
+        .slack_errors
+        .insert("U_GONE".to_string(), "user_not_found".to_string());
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("check");
+
+    let env = [
+        ("TRELLO_BOARD_IDS", "board1,board2"),
+        ("TRELLO_REVIEW_LISTS", "Reveiw"),
+        ("TRELLO_INACTIVE_CARDS_LISTS", "Sprint"),
+        ("USER_MAPPING", "alice=U_ALICE,bob=U_GONE"),
+    ];
+
+    let output = run(&server, &directory, &env, &["check"]);
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Checked 1 boards with 3 lists and 3 members"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Board board2 could not be fetched"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(
+            "Review list \"Reveiw\" does not exist on board Team Board (board1), did you mean \"Review\"?"
+        ),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("\"Sprint\""), "{stdout}");
+    assert!(
+        stdout.contains("Trello user carol (CAROL) is not mapped to a Slack user"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Slack user U_GONE of Trello user bob could not be resolved"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("U_ALICE"), "{stdout}");
+
+    let output = run(&server, &directory, ENV, &["check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "carol is still unmapped");
+    assert!(!stdout.contains("does not exist"), "{stdout}");
+}
+
+#[test]
+fn it_reports_failed_recipients_after_notifying_the_others() {
+    let mut fixtures = board_fixtures();
+    fixtures
+        .slack_errors
+        .insert("U_BOB".to_string(), "channel_not_found".to_string());
+    let server = MockServer::start(fixtures);
+    let directory = working_directory("failed-recipients");
+
+    let output = run(&server, &directory, ENV, &["pending-reviews"]);
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Slack user U_BOB"), "{stderr}");
+    assert!(stderr.contains("channel_not_found"), "{stderr}");
+
+    let messages = server.slack_messages();
+    assert_eq!(messages["U_ALICE"].len(), 1, "Alice is notified anyway");
+}
+
+#[test]
+fn it_does_not_post_in_a_dry_run() {
+    let server = MockServer::start(board_fixtures());
+    let directory = working_directory("dry-run");
+
+    let mut env = ENV.to_vec();
+    env.push(("SLACK_BOT_TOKEN", "")); // not needed without posting
+    let output = run(
+        &server,
+        &directory,
+        &env,
+        &[
+            "--dry-run",
+            "--dry-run-output=messages.md",
+            "pending-reviews",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(server.slack_messages().is_empty());
+
+    let rendered = std::fs::read_to_string(directory.join("messages.md")).unwrap();
+    assert!(rendered.contains("Message to Slack user U_ALICE (Trello user alice)"));
+    assert!(rendered.contains("Message to Slack user U_BOB (Trello user bob)"));
+}
+
+#[test]
+fn it_renders_custom_templates_with_sample_data() {
+    let server = MockServer::start(Fixtures::default());
+    let directory = working_directory("render-template");
+    std::fs::create_dir_all(directory.join("templates")).expect("should create template directory");
+    std::fs::write(
+        directory.join("templates/pending-reviews.md"),
+        "{{ user.slack_user }}: {% for review in reviews %}{{ review.card_name }}; {% endfor %}",
+    )
+    .expect("should write template");
+
+    let output = run(
+        &server,
+        &directory,
+        &[
+            ("TEMPLATE_DIR", "templates"),
+            ("USER_MAPPING", "alice=U_ALICE"),
+        ],
+        &["render-template", "pending-reviews"],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "U_ALICE: Sample card 1; Sample card 2; Sample card 3; "
+    );
+    assert!(server.slack_messages().is_empty());
+}